@@ -15,9 +15,16 @@ unsafe extern "C" fn dlopen(filename: *const c_char, flags: c_int) -> *const c_v
     dlopen_rs::dlopen::dlopen(filename, flags)
 }
 
+// 直接跳转到dlopen_rs的dlsym，保留调用者的返回地址，RTLD_NEXT需要用它找到调用者
 #[no_mangle]
-unsafe extern "C" fn dlsym(handle: *const c_void, symbol_name: *const c_char) -> *const c_void {
-    dlopen_rs::dlopen::dlsym(handle, symbol_name)
+#[unsafe(naked)]
+unsafe extern "C" fn dlsym(_handle: *const c_void, _symbol_name: *const c_char) -> *const c_void {
+    #[cfg(target_arch = "x86_64")]
+    core::arch::naked_asm!("jmp {}", sym dlopen_rs::dlopen::dlsym);
+    #[cfg(target_arch = "aarch64")]
+    core::arch::naked_asm!("b {}", sym dlopen_rs::dlopen::dlsym);
+    #[cfg(target_arch = "riscv64")]
+    core::arch::naked_asm!("tail {}", sym dlopen_rs::dlopen::dlsym);
 }
 
 #[no_mangle]
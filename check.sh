@@ -1,4 +1,6 @@
 cargo check -p dlopen-rs  --no-default-features --features=""
+# 检查no_std的构建
+cargo build -p dlopen-rs  --no-default-features
 cargo check -p dlopen-rs  --no-default-features --features="std"
 cargo check -p dlopen-rs  --no-default-features --features="tls"
 # 检查unwind相关的feature
@@ -0,0 +1,115 @@
+//! Contain `abort` and `__stack_chk_fail` called by the loaded dynamic libraries.
+//!
+//! By default, a dynamic library that calls `abort` or trips a stack canary terminates the whole process.
+//! After calling [`install_abort_handler`], these two functions are replaced with builtin symbols
+//! for libraries relocated afterwards. When they are called inside [`catch_abort`], the stack is
+//! unwound back to `catch_abort` instead of terminating the process.
+//!
+//! # Caveats
+//! * The frames of the dynamic library are discarded without running any of its cleanup code,
+//!   so locks it holds are never released and the memory it allocated may be leaked.
+//!   The state of the library (and of the process) may be inconsistent after a recovery,
+//!   it is recommended to stop using the library afterwards.
+//! * Unwinding requires unwind tables for all frames between `catch_abort` and the failing call.
+//!   C code must be compiled with `-fexceptions` or `-funwind-tables`.
+//! * A stack canary failure means the stack has been corrupted, recovering from it is only a best effort.
+//! * Outside of `catch_abort`, the builtin symbols behave like the original ones and terminate the process.
+use crate::{panic_error, register_builtin, Dylib, Result};
+use std::{
+    cell::Cell,
+    panic::{catch_unwind, resume_unwind, AssertUnwindSafe},
+};
+
+/// The reason why a call in [`catch_abort`] was aborted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PluginAbort {
+    /// The dynamic library called `abort`.
+    Abort,
+    /// The dynamic library called `__stack_chk_fail`.
+    StackChkFail,
+}
+
+thread_local! {
+    static RECOVERY_DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+#[cold]
+fn recover(kind: PluginAbort) -> ! {
+    if RECOVERY_DEPTH.get() == 0 {
+        log::error!("[{:?}] is called without a recovery point", kind);
+        unsafe { libc::abort() }
+    }
+    log::error!("[{:?}] is called, unwinding to the recovery point", kind);
+    resume_unwind(Box::new(kind))
+}
+
+extern "C-unwind" fn abort_impl() -> ! {
+    recover(PluginAbort::Abort)
+}
+
+extern "C-unwind" fn stack_chk_fail_impl() -> ! {
+    recover(PluginAbort::StackChkFail)
+}
+
+/// Replace `abort` and `__stack_chk_fail` with recoverable builtin symbols.
+/// It only affects the dynamic libraries relocated after it is called.
+pub fn install_abort_handler() {
+    unsafe {
+        register_builtin("abort", abort_impl as _);
+        register_builtin("__stack_chk_fail", stack_chk_fail_impl as _);
+    }
+}
+
+/// Call `f` and recover if a dynamic library calls `abort` or `__stack_chk_fail` during the call.
+/// Please read the [module documentation](self) for the caveats.
+///
+/// # Examples
+/// ```no_run
+/// # use dlopen_rs::{ElfLibrary, OpenFlags};
+/// dlopen_rs::install_abort_handler();
+/// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+/// let run = unsafe { lib.get::<extern "C-unwind" fn()>("run").unwrap() };
+/// if let Err(kind) = dlopen_rs::catch_abort(|| run()) {
+///     println!("the plugin is aborted: {:?}", kind);
+/// }
+/// ```
+pub fn catch_abort<R>(f: impl FnOnce() -> R) -> core::result::Result<R, PluginAbort> {
+    RECOVERY_DEPTH.set(RECOVERY_DEPTH.get() + 1);
+    let res = catch_unwind(AssertUnwindSafe(f));
+    RECOVERY_DEPTH.set(RECOVERY_DEPTH.get() - 1);
+    match res {
+        Ok(val) => Ok(val),
+        Err(payload) => match payload.downcast::<PluginAbort>() {
+            Ok(kind) => Err(*kind),
+            Err(payload) => resume_unwind(payload),
+        },
+    }
+}
+
+impl Dylib<'_> {
+    /// Call `f`, which calls into the dynamic library, and turn a panic unwinding out of it into an error.
+    ///
+    /// It only works if the dynamic library is compiled with `panic=unwind` and its unwind tables are registered,
+    /// see the `libgcc`, `libunwind` and `unwinding` features. A library compiled with `panic=abort` still aborts the process.
+    /// Like [`catch_abort`], the state of the library may be inconsistent after a panic.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let run = unsafe { lib.get::<extern "C-unwind" fn()>("run").unwrap() };
+    /// if let Err(err) = lib.call_catch(|| run()) {
+    ///     println!("the plugin panicked: {}", err);
+    /// }
+    /// ```
+    pub fn call_catch<R>(&self, f: impl FnOnce() -> R) -> Result<R> {
+        catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+            let msg = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| payload.downcast_ref::<String>().map(|msg| msg.as_str()))
+                .unwrap_or("unknown panic payload");
+            panic_error(format!("[{}] panicked: {}", self.name(), msg))
+        })
+    }
+}
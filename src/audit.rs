@@ -0,0 +1,66 @@
+use crate::Dylib;
+use alloc::sync::Arc;
+use spin::RwLock;
+
+/// Notifications about loading and binding, a subset of the `rtld-audit` interface of glibc. See [`set_auditor`].
+///
+/// Only two hooks are supported:
+/// * `objopen` (`la_objopen`) is called when a library has been loaded and relocated by `dlopen` or `relocate`.
+///   The dependencies loaded by `dlopen` are not reported individually.
+/// * `symbind` (`la_symbind`) is called before each symbol lookup made while relocating a library eagerly,
+///   and it can redirect the symbol. Lazy binding does not call it.
+pub trait Auditor {
+    /// A library has been loaded and relocated.
+    fn objopen(&self, lib: &Dylib) {
+        let _ = lib;
+    }
+
+    /// The symbol `name` is needed by the library `from`. Return a non-null address to bind it to that address,
+    /// or null to resolve it as usual.
+    fn symbind(&self, name: &str, from: &str) -> *const () {
+        let _ = (name, from);
+        core::ptr::null()
+    }
+}
+
+static AUDITOR: RwLock<Option<Arc<dyn Auditor + Send + Sync>>> = RwLock::new(None);
+
+/// Install an [`Auditor`], it replaces the previously installed one. Pass `None` to remove it.
+///
+/// # Note
+/// The auditor is called while dlopen-rs may hold its internal locks, so it must not load or unload libraries.
+///
+/// # Examples
+/// ```no_run
+/// use dlopen_rs::{Auditor, Dylib};
+/// use std::sync::Arc;
+///
+/// struct Monitor;
+///
+/// impl Auditor for Monitor {
+///     fn objopen(&self, lib: &Dylib) {
+///         println!("load {} at 0x{:x}", lib.name(), lib.base());
+///     }
+/// }
+///
+/// dlopen_rs::set_auditor(Some(Arc::new(Monitor)));
+/// ```
+pub fn set_auditor(auditor: Option<Arc<dyn Auditor + Send + Sync>>) {
+    *AUDITOR.write() = auditor;
+}
+
+#[inline]
+pub(crate) fn objopen(lib: &Dylib) {
+    if let Some(auditor) = AUDITOR.read().as_ref() {
+        auditor.objopen(lib);
+    }
+}
+
+#[inline]
+pub(crate) fn symbind(name: &str, from: &str) -> Option<*const ()> {
+    AUDITOR
+        .read()
+        .as_ref()
+        .map(|auditor| auditor.symbind(name, from))
+        .filter(|addr| !addr.is_null())
+}
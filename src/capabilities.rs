@@ -0,0 +1,76 @@
+/// The unwind library used to register the `.eh_frame` of loaded libraries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnwindBackend {
+    /// The `libgcc` feature is enabled.
+    Libgcc,
+    /// The `libunwind` feature is enabled.
+    Libunwind,
+    /// The `unwinding` feature is enabled.
+    Unwinding,
+    /// No unwind library is used, exceptions can not propagate out of loaded libraries.
+    None,
+}
+
+/// What the linked dlopen-rs supports, see [`capabilities`].
+#[derive(Clone, Debug)]
+pub struct Capabilities {
+    /// The version of dlopen-rs.
+    pub version: &'static str,
+    /// The target architecture.
+    pub arch: &'static str,
+    /// The unwind library in use.
+    pub unwind: UnwindBackend,
+    /// Whether the `tls` feature is enabled.
+    pub tls: bool,
+    /// Whether the `version` feature (versioned symbol lookup) is enabled.
+    pub symbol_version: bool,
+    /// Whether the `debug` feature (gdb/lldb support) is enabled.
+    pub debug: bool,
+    /// Whether the `mmap` feature is enabled.
+    pub mmap: bool,
+    /// Whether the `std` feature is enabled.
+    pub std: bool,
+    /// The page size in use. It is `None` in the `no_std` environment.
+    pub page_size: Option<usize>,
+}
+
+/// Report the features compiled into dlopen-rs and the capabilities of the running system.
+///
+/// # Examples
+/// ```
+/// let caps = dlopen_rs::capabilities();
+/// println!("{:?}", caps);
+/// ```
+pub fn capabilities() -> Capabilities {
+    let unwind = if cfg!(feature = "libgcc") {
+        UnwindBackend::Libgcc
+    } else if cfg!(feature = "libunwind") {
+        UnwindBackend::Libunwind
+    } else if cfg!(feature = "unwinding") {
+        UnwindBackend::Unwinding
+    } else {
+        UnwindBackend::None
+    };
+    let arch = if cfg!(target_arch = "x86_64") {
+        "x86_64"
+    } else if cfg!(target_arch = "aarch64") {
+        "aarch64"
+    } else {
+        "riscv64"
+    };
+    #[cfg(feature = "std")]
+    let page_size = Some(unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize);
+    #[cfg(not(feature = "std"))]
+    let page_size = None;
+    Capabilities {
+        version: env!("CARGO_PKG_VERSION"),
+        arch,
+        unwind,
+        tls: cfg!(feature = "tls"),
+        symbol_version: cfg!(feature = "version"),
+        debug: cfg!(feature = "debug"),
+        mmap: cfg!(feature = "mmap"),
+        std: cfg!(feature = "std"),
+        page_size,
+    }
+}
@@ -1,3 +1,19 @@
+//! Register the dynamic libraries loaded by dlopen-rs in the `_r_debug` link map of the system dynamic linker,
+//! so that gdb and lldb can find them.
+//!
+//! Every library loaded with the `debug` feature, whether by `dlopen` or by `from_file` and `relocate`,
+//! is appended to the link map with its `l_addr`, `l_ld` and `l_name`, and removed when it is destroyed.
+//! The debugger reads the symbols from the file named by `l_name`, so libraries loaded from bytes
+//! or by a relative path can not be found by it.
+//!
+//! For example, `info sharedlibrary` in gdb lists the libraries loaded by dlopen-rs,
+//! and `break` and `bt` work with the functions in them:
+//! ```text
+//! (gdb) break awesome_function
+//! (gdb) run
+//! (gdb) info sharedlibrary
+//! (gdb) bt
+//! ```
 use crate::init::{Debug, LinkMap};
 use core::{
     ffi::{c_int, CStr},
@@ -65,6 +81,11 @@ pub(crate) static DEBUG: Mutex<CustomDebug> = Mutex::new(CustomDebug {
 });
 
 impl DebugInfo {
+    #[inline]
+    pub(crate) fn link_map(&self) -> *const LinkMap {
+        self.link_map.as_ref()
+    }
+
     pub(crate) unsafe fn new(base: usize, name: *const i8, dynamic: usize) -> DebugInfo {
         let mut custom_debug = DEBUG.lock().unwrap();
         let tail = custom_debug.tail;
@@ -72,6 +93,9 @@ impl DebugInfo {
             panic!("Please call init function first");
         }
         let debug = &mut *custom_debug.debug;
+        // 按照r_debug的协议，修改link_map链表之前先通知调试器
+        debug.state = RT_ADD;
+        (debug.brk)();
         let link_map = Box::leak(Box::new(LinkMap {
             l_addr: base as _,
             l_name: name as _,
@@ -85,8 +109,6 @@ impl DebugInfo {
             (*tail).l_next = link_map;
         }
         custom_debug.tail = link_map;
-        debug.state = RT_ADD;
-        (debug.brk)();
         debug.state = RT_CONSISTENT;
         (debug.brk)();
         log::trace!(
@@ -1,10 +1,21 @@
 use crate::{
-    loader::{builtin, create_lazy_scope, deal_unknown, Dylib, ElfLibrary},
-    register::{register, IS_RELOCATED, MANAGER},
+    audit,
+    event::{emit, LoadEvent},
+    loader::{
+        builtin, create_lazy_scope, deal_unknown, deferred, filter, find_strong, symbolic,
+        symkind::StrongScope, Dylib, ElfLibrary,
+    },
+    register::{new_namespace, register, registry_name, Lmid, Manager, LM_ID_NEWLM, MANAGER},
     OpenFlags, Result,
 };
-use alloc::{borrow::ToOwned, sync::Arc, vec::Vec};
+use alloc::{borrow::ToOwned, string::String, sync::Arc, vec::Vec};
 use core::marker::PhantomData;
+use elf_loader::CoreComponent;
+use spin::RwLock;
+#[cfg(feature = "std")]
+use std::path::PathBuf;
+
+type Interpose<'a> = &'a dyn Fn(&str) -> Option<*const ()>;
 
 impl ElfLibrary {
     /// Load a shared library from a specified path. It is the same as dlopen.
@@ -20,9 +31,143 @@ impl ElfLibrary {
     #[cfg(feature = "std")]
     #[inline]
     pub fn dlopen(path: impl AsRef<std::ffi::OsStr>, flags: OpenFlags) -> Result<Dylib<'static>> {
-        dlopen_impl(path.as_ref().to_str().unwrap(), flags, || {
-            ElfLibrary::from_file(path.as_ref(), flags)
-        })
+        dlopen_impl(
+            path.as_ref().to_str().unwrap(),
+            flags,
+            &MANAGER,
+            &[],
+            None,
+            || ElfLibrary::from_file(path.as_ref(), flags),
+        )
+    }
+
+    /// Get the library registered as `shortname` (see `ElfLibrary::load_existing`), or load it from `path` with dlopen
+    /// if it has not been loaded yet. The file is not opened if the library has already been loaded.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlopen_rs::{ElfLibrary, OpenFlags};
+    ///
+    /// let lib = ElfLibrary::get_or_load("libfoo.so", "/opt/foo/libfoo.so", OpenFlags::RTLD_NOW).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn get_or_load(
+        shortname: &str,
+        path: impl AsRef<std::ffi::OsStr>,
+        flags: OpenFlags,
+    ) -> Result<Dylib<'static>> {
+        let existing = MANAGER
+            .read()
+            .all
+            .get(shortname)
+            .filter(|lib| lib.deps().is_some())
+            .map(|lib| lib.get_dylib(&MANAGER));
+        if let Some(lib) = existing {
+            return Ok(lib);
+        }
+        ElfLibrary::dlopen(path, flags)
+    }
+
+    /// Load a shared library whose symbols take precedence over those of all other libraries, like `LD_PRELOAD`.
+    ///
+    /// The library is opened with `RTLD_GLOBAL` and placed in front of the global scope (after the libraries preloaded before it),
+    /// so it is searched first when relocating the libraries loaded afterwards and during lazy binding.
+    /// The libraries that have already been relocated are not affected.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlopen_rs::{ElfLibrary, OpenFlags};
+    ///
+    /// let _mock = ElfLibrary::preload("/path/to/libmockmalloc.so").unwrap();
+    /// let lib = ElfLibrary::dlopen("/path/to/libplugin.so", OpenFlags::RTLD_NOW).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn preload(path: impl AsRef<std::ffi::OsStr>) -> Result<Dylib<'static>> {
+        let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_GLOBAL | OpenFlags::RTLD_NOW)?;
+        let mut lock = MANAGER.write();
+        if let Some(idx) = lock.global.get_index_of(registry_name(&lib.inner)) {
+            let preloaded = lock.preloaded;
+            if idx >= preloaded {
+                log::debug!("Preload dylib [{}]", lib.name());
+                lock.global.move_index(idx, preloaded);
+                lock.preloaded += 1;
+            }
+        }
+        Ok(lib)
+    }
+
+    /// Load a shared library into the link-map namespace `nsid`. It is the same as dlmopen.
+    ///
+    /// Libraries in different namespaces do not share symbols, so the same library can be loaded once in each namespace.
+    /// `nsid` can be `LM_ID_BASE`, the namespace used by `dlopen`, `LM_ID_NEWLM`, which creates a new namespace,
+    /// or the namespace of a library loaded before (see `Dylib::namespace`).
+    ///
+    /// # Note
+    /// The libraries loaded by the program itself (see `init`) are shared by all namespaces, because they can not be loaded twice.
+    /// Lazy binding uses the global scope of `LM_ID_BASE` after the dependencies of the library.
+    /// Namespaces are never destroyed.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlopen_rs::{ElfLibrary, OpenFlags, LM_ID_NEWLM};
+    ///
+    /// let v1 = ElfLibrary::dlmopen(LM_ID_NEWLM, "/path/to/v1/libplugin.so", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let v2 = ElfLibrary::dlmopen(LM_ID_NEWLM, "/path/to/v2/libplugin.so", OpenFlags::RTLD_LOCAL).unwrap();
+    /// assert_ne!(v1.namespace(), v2.namespace());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn dlmopen(
+        nsid: Lmid,
+        path: impl AsRef<std::ffi::OsStr>,
+        flags: OpenFlags,
+    ) -> Result<Dylib<'static>> {
+        let manager = if nsid == LM_ID_NEWLM {
+            new_namespace()
+        } else {
+            crate::register::namespace(nsid).ok_or(crate::find_lib_error(alloc::format!(
+                "invalid namespace: {}",
+                nsid
+            )))?
+        };
+        dlopen_impl(
+            path.as_ref().to_str().unwrap(),
+            flags,
+            manager,
+            &[],
+            None,
+            || ElfLibrary::from_file(path.as_ref(), flags),
+        )
+    }
+
+    /// Load a shared library from a specified path without blocking the async runtime. It is the same as dlopen.
+    ///
+    /// The library is loaded on the blocking thread pool of tokio with `spawn_blocking`, so it must be called
+    /// within a tokio runtime. The loading itself is not asynchronous.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use dlopen_rs::{ElfLibrary, OpenFlags};
+    ///
+    /// # async fn load() {
+    /// let lib = ElfLibrary::dlopen_async("/path/to/library.so", OpenFlags::RTLD_LOCAL)
+    ///     .await
+    ///     .unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "async")]
+    pub async fn dlopen_async(
+        path: impl AsRef<std::ffi::OsStr>,
+        flags: OpenFlags,
+    ) -> Result<Dylib<'static>> {
+        // Dylib没有实现Send，但它只是被转移到调用者的线程，不会被共享
+        struct Loaded(Result<Dylib<'static>>);
+        unsafe impl Send for Loaded {}
+
+        let path = std::path::PathBuf::from(path.as_ref());
+        tokio::task::spawn_blocking(move || Loaded(ElfLibrary::dlopen(path, flags)))
+            .await
+            .map_err(|err| crate::panic_error(alloc::format!("dlopen_async failed: {}", err)))?
+            .0
     }
 
     /// Load a shared library from bytes. It is the same as dlopen. However, it can also be used in the no_std environment,
@@ -33,53 +178,137 @@ impl ElfLibrary {
         path: impl AsRef<str>,
         flags: OpenFlags,
     ) -> Result<Dylib> {
-        dlopen_impl(path.as_ref(), flags, || {
+        dlopen_impl(path.as_ref(), flags, &MANAGER, &[], None, || {
             ElfLibrary::from_binary(bytes, path.as_ref(), flags)
         })
     }
 }
 
-struct Recycler {
-    is_recycler: bool,
-    old_all_len: usize,
-    old_global_len: usize,
+#[inline]
+fn resolve_alias<'a>(aliases: &'a [(&str, String)], name: &'a str) -> &'a str {
+    aliases
+        .iter()
+        .find(|(needed, _)| *needed == name)
+        .map_or(name, |(_, soname)| soname.as_str())
 }
 
-impl Drop for Recycler {
-    fn drop(&mut self) {
-        if self.is_recycler {
-            log::debug!("Destroying newly added dynamic libraries");
-            let mut lock = MANAGER.write();
-            lock.all.truncate(self.old_all_len);
-            lock.global.truncate(self.old_global_len);
-        }
+#[inline]
+fn in_deps(dep_libs: &[CoreComponent], name: &str) -> bool {
+    dep_libs.iter().any(|dep| registry_name(dep) == name)
+}
+
+/// Record the alias if the `DT_SONAME` of the newly loaded `core` is different from `lib_name` in `DT_NEEDED`.
+/// It returns `true` if a library with the same `DT_SONAME` has already been loaded, and then `core` should be discarded.
+fn add_alias<'a>(
+    aliases: &mut Vec<(&'a str, String)>,
+    manager: &Manager,
+    dep_libs: &mut Vec<CoreComponent>,
+    lib_name: &'a str,
+    core: &CoreComponent,
+) -> bool {
+    let soname = registry_name(core);
+    if soname == lib_name {
+        return false;
+    }
+    aliases.push((lib_name, soname.to_owned()));
+    if in_deps(dep_libs, soname) {
+        log::debug!(
+            "[{}] has already been loaded as [{}], discard the new one",
+            lib_name,
+            soname
+        );
+        return true;
     }
+    if let Some(lib) = manager.all.get(soname) {
+        log::debug!(
+            "[{}] has already been loaded as [{}], discard the new one",
+            lib_name,
+            soname
+        );
+        dep_libs.push(lib.core_component());
+        return true;
+    }
+    false
 }
 
-fn dlopen_impl(
+/// Wait until the init functions of a library and its dependencies called by other threads return.
+fn wait_init(lib: &Dylib) {
+    match lib.deps.as_deref() {
+        Some(deps) => deps.iter().for_each(deferred::wait),
+        None => deferred::wait(&lib.inner),
+    }
+}
+
+// 加载和重定位都不持有写锁，只在最后注册时获取写锁，因此不相关的库可以被并发地加载。
+// 如果在此期间其他线程注册了同名的库，则以先注册的为准，丢弃新加载的库后重新加载。
+// 新库的init函数在注册之后才调用，因此被丢弃的库不会被初始化。
+pub(crate) fn dlopen_impl(
     path: &str,
-    mut flags: OpenFlags,
+    flags: OpenFlags,
+    manager: &'static RwLock<Manager>,
+    // 在其他路径之前查找依赖库的目录
+    search_paths: &[String],
+    // 在重定位这次加载的所有库时优先使用的符号
+    interpose: Option<Interpose<'_>>,
     f: impl Fn() -> Result<ElfLibrary>,
 ) -> Result<Dylib<'static>> {
+    loop {
+        if let Some(lib) = try_dlopen(path, flags, manager, search_paths, interpose, &f)? {
+            return Ok(lib);
+        }
+        log::debug!(
+            "Some dependencies of [{}] have been loaded by another thread, load it again",
+            path
+        );
+    }
+}
+
+// 返回None表示有依赖库被其他线程抢先注册了，新加载的库已经被丢弃，需要重新加载
+fn try_dlopen(
+    path: &str,
+    mut flags: OpenFlags,
+    manager: &'static RwLock<Manager>,
+    #[cfg_attr(not(feature = "std"), allow(unused))] search_paths: &[String],
+    interpose: Option<Interpose<'_>>,
+    f: &impl Fn() -> Result<ElfLibrary>,
+) -> Result<Option<Dylib<'static>>> {
     let shortname = path.split('/').last().unwrap();
     log::info!("dlopen: Try to open [{}] with [{:?}] ", path, flags);
-    let reader = MANAGER.read();
+    let reader = manager.read();
     // 新加载的动态库
     let mut new_libs = Vec::new();
     #[cfg(feature = "std")]
     let mut rpath_vec = Vec::new();
     // 检查是否是已经加载的库
-    let core = if let Some(lib) = reader.all.get(shortname) {
+    let mut existing = reader.all.get(shortname);
+    let mut new_lib = None;
+    if existing.is_none() {
+        let lib = f()?;
+        // 库以DT_SONAME注册，它可能已经通过其他路径（比如符号链接）加载过了
+        // 只读取user_data中的DT_SONAME，不会使用未重定位的库中的符号
+        existing = reader
+            .all
+            .get(registry_name(unsafe { lib.dylib.core_component_ref() }));
+        if existing.is_some() {
+            log::debug!("[{}] has already been loaded with the same soname", path);
+        } else {
+            new_lib = Some(lib);
+        }
+    }
+    let core = if let Some(lib) = existing {
         if lib.deps().is_some()
             && !flags
                 .difference(lib.flags())
                 .contains(OpenFlags::RTLD_GLOBAL)
         {
-            return Ok(lib.get_dylib());
+            let res = lib.get_dylib(manager);
+            drop(reader);
+            wait_init(&res);
+            return Ok(Some(res));
         }
         lib.core_component()
     } else {
-        let lib = f()?;
+        let lib = new_lib.unwrap();
         let core = unsafe { lib.dylib.core_component().clone() };
         #[cfg(feature = "std")]
         rpath_vec.push(
@@ -93,53 +322,52 @@ fn dlopen_impl(
     };
 
     drop(reader);
+    let is_new = !new_libs.is_empty();
 
     if flags.contains(OpenFlags::CUSTOM_NOT_REGISTER) {
         log::warn!("dlopen ignores the open flag CUSTOM_NOT_REGISTER");
         flags.remove(OpenFlags::CUSTOM_NOT_REGISTER);
     }
 
-    let mut recycler = Recycler {
-        is_recycler: true,
-        old_all_len: usize::MAX,
-        old_global_len: usize::MAX,
-    };
-
     // 用于保存所有的依赖库
     let mut dep_libs = Vec::new();
+    // new_libs中的库在dep_libs中的位置
+    let mut new_pos = Vec::new();
+    // 已经加载的库中需要变为RTLD_GLOBAL的库
+    let mut promoted = Vec::new();
+    // DT_NEEDED中的名字与DT_SONAME不同的库
+    let mut aliases: Vec<(&str, String)> = Vec::new();
     let mut cur_pos = 0;
+    if is_new {
+        new_pos.push(0);
+    }
     dep_libs.push(core);
-    let mut lock = MANAGER.write();
-    recycler.old_all_len = lock.all.len();
-    recycler.old_global_len = lock.global.len();
 
     #[cfg(feature = "std")]
     let mut cur_rpath_pos = 0;
     // 广度优先搜索，这是规范的要求，这个循环里会加载所有需要的动态库，无论是直接依赖还是间接依赖的
     while cur_pos < dep_libs.len() {
         let lib_names: &[&str] = unsafe { core::mem::transmute(dep_libs[cur_pos].needed_libs()) };
+        // DT_FILTER的filtee必须被加载，而DT_AUXILIARY的filtee加载失败时会被忽略
+        let filtees = unsafe { filter::filtees(&dep_libs[cur_pos]) };
         #[cfg(feature = "std")]
         let mut cur_rpath = None;
-        for lib_name in lib_names {
-            if let Some(lib) = lock.all.get_mut(*lib_name) {
-                if !lib.is_mark {
-                    lib.is_mark = true;
-                    dep_libs.push(lib.core_component());
-                    if flags
-                        .difference(lib.flags())
-                        .contains(OpenFlags::RTLD_GLOBAL)
-                    {
-                        let shortname = lib.core_component_ref().shortname().to_owned();
-                        log::debug!(
-							"Trying to update a library. Name: [{}] Old flags:[{:?}] New flags:[{:?}]",
-							shortname,
-							lib.flags(),
-							flags
-						);
-                        lib.set_flags(flags);
-                        let core = lib.core_component();
-                        lock.global.insert(shortname, core);
-                    }
+        for (lib_name, required) in lib_names
+            .iter()
+            .map(|name| (*name, true))
+            .chain(filtees.iter().map(|(name, aux)| (*name, !aux)))
+        {
+            let name = resolve_alias(&aliases, lib_name);
+            if in_deps(&dep_libs, name) {
+                continue;
+            }
+            if let Some(lib) = manager.read().all.get(name) {
+                dep_libs.push(lib.core_component());
+                if flags
+                    .difference(lib.flags())
+                    .contains(OpenFlags::RTLD_GLOBAL)
+                {
+                    promoted.push(name.to_owned());
                 }
                 continue;
             }
@@ -155,43 +383,107 @@ fn dlopen_impl(
                     pos
                 };
 
-                imp::find_library(
-                    rpath,
-                    &mut rpath_vec,
-                    lib_name,
-                    |file, file_path, rpath_vec| {
-                        let new_lib =
-                            ElfLibrary::from_open_file(file, file_path.to_str().unwrap(), flags)?;
-                        let inner = unsafe { new_lib.dylib.core_component().clone() };
-                        // 最多一次性加载255个新库
-                        assert!(new_libs.len() < IS_RELOCATED as usize);
-                        register(
-                            inner.clone(),
-                            flags,
-                            None,
-                            &mut lock,
-                            true,
-                            Some(new_libs.len() as _),
-                        );
-                        dep_libs.push(inner);
-                        rpath_vec.push(
-                            new_lib
-                                .dylib
-                                .rpath()
-                                .map(|rpath| imp::fixup_rpath(new_lib.name(), rpath))
-                                .unwrap_or(Box::new([])),
-                        );
-                        new_libs.push(Some(new_lib));
-                        Ok(())
-                    },
-                )?;
+                let mut add = |new_lib: ElfLibrary, rpath_vec: &mut Vec<Box<[PathBuf]>>| {
+                    let inner = unsafe { new_lib.dylib.core_component().clone() };
+                    if add_alias(
+                        &mut aliases,
+                        &manager.read(),
+                        &mut dep_libs,
+                        lib_name,
+                        &inner,
+                    ) {
+                        return;
+                    }
+                    new_pos.push(dep_libs.len());
+                    dep_libs.push(inner);
+                    rpath_vec.push(
+                        new_lib
+                            .dylib
+                            .rpath()
+                            .map(|rpath| imp::fixup_rpath(new_lib.name(), rpath))
+                            .unwrap_or(Box::new([])),
+                    );
+                    new_libs.push(Some(new_lib));
+                };
+
+                // 使用者设置的解析器优先于默认的查找顺序
+                let res = (|| -> Result<()> {
+                    match imp::resolve_dependency(lib_name) {
+                        Some(DepSource::Skip) => {
+                            log::debug!("Skip the dependency [{}]", lib_name);
+                            Ok(())
+                        }
+                        Some(DepSource::Path(file_path)) => {
+                            let file = std::fs::File::open(&file_path)?;
+                            let new_lib = ElfLibrary::from_open_file(
+                                file,
+                                file_path.to_str().unwrap(),
+                                flags,
+                            )?;
+                            add(new_lib, &mut rpath_vec);
+                            Ok(())
+                        }
+                        Some(DepSource::Bytes(bytes)) => {
+                            let new_lib = ElfLibrary::from_binary(&bytes, lib_name, flags)?;
+                            add(new_lib, &mut rpath_vec);
+                            Ok(())
+                        }
+                        None => imp::find_library(
+                            search_paths,
+                            rpath,
+                            &mut rpath_vec,
+                            lib_name,
+                            |file, file_path, rpath_vec| {
+                                let new_lib = ElfLibrary::from_open_file(
+                                    file,
+                                    file_path.to_str().unwrap(),
+                                    flags,
+                                )?;
+                                add(new_lib, rpath_vec);
+                                Ok(())
+                            },
+                        ),
+                    }
+                })();
+                match res {
+                    Err(err) if !required => {
+                        log::debug!("Ignore the auxiliary filtee [{}]: {}", lib_name, err);
+                    }
+                    res => res?,
+                }
             }
 
             #[cfg(not(feature = "std"))]
-            return Err(crate::find_lib_error(alloc::format!(
-                "can not find file: {}",
-                lib_name
-            )));
+            {
+                let staged = crate::loader::staged::STAGED_BINARY.read();
+                let Some(bytes) = staged.get(lib_name) else {
+                    if !required {
+                        continue;
+                    }
+                    return Err(crate::find_lib_error(alloc::format!(
+                        "can not find file: {}",
+                        lib_name
+                    )));
+                };
+                let new_lib = match ElfLibrary::from_binary(bytes, lib_name, flags) {
+                    Ok(new_lib) => new_lib,
+                    Err(_) if !required => continue,
+                    Err(err) => return Err(err),
+                };
+                let inner = unsafe { new_lib.dylib.core_component().clone() };
+                if add_alias(
+                    &mut aliases,
+                    &manager.read(),
+                    &mut dep_libs,
+                    lib_name,
+                    &inner,
+                ) {
+                    continue;
+                }
+                new_pos.push(dep_libs.len());
+                dep_libs.push(inner);
+                new_libs.push(Some(new_lib));
+            }
         }
         cur_pos += 1;
     }
@@ -203,80 +495,183 @@ fn dlopen_impl(
     }
     // 保存new_libs的索引
     let mut stack = Vec::new();
-    stack.push(Item { idx: 0, next: 0 });
+    // new_libs中的库是否已经（或正在）被重定位
+    let mut visited = alloc::vec![false; new_libs.len()];
+    if is_new {
+        visited[0] = true;
+        stack.push(Item { idx: 0, next: 0 });
+    }
+    // 重定位的顺序，也是调用init函数的顺序
+    let mut order = Vec::new();
+    // 重定位时使用的全局符号表的快照
+    let global: Vec<CoreComponent> = manager.read().global.values().cloned().collect();
+    // filtee位于filter之前，这样filter中的符号会优先从filtee中查找
+    let scope = filter::scope(&dep_libs);
+    let strong = StrongScope::new(global.iter().chain(scope.iter()));
 
     while let Some(mut item) = stack.pop() {
-        let names = new_libs[item.idx].as_ref().unwrap().needed_libs();
+        let names: Vec<&str> = new_libs[item.idx]
+            .as_ref()
+            .unwrap()
+            .needed_libs()
+            .iter()
+            .copied()
+            .chain(
+                unsafe { filter::filtees(&dep_libs[new_pos[item.idx]]) }
+                    .into_iter()
+                    .map(|(name, _)| name),
+            )
+            .collect();
         let mut can_relocate = true;
         for name in names.iter().skip(item.next) {
-            let lib = lock.all.get_mut(*name).unwrap();
-            let idx = lib.new_idx;
-            lib.is_mark = false;
             item.next += 1;
-            if idx == IS_RELOCATED {
+            let name = resolve_alias(&aliases, name);
+            // 不在new_libs中的库已经被重定位过了
+            let Some(idx) = new_pos
+                .iter()
+                .position(|pos| registry_name(&dep_libs[*pos]) == name)
+            else {
+                continue;
+            };
+            if visited[idx] {
                 continue;
             }
-            lib.new_idx = IS_RELOCATED;
+            visited[idx] = true;
             stack.push(item);
-            stack.push(Item {
-                idx: idx as usize,
-                next: 0,
-            });
+            stack.push(Item { idx, next: 0 });
             can_relocate = false;
             break;
         }
         if can_relocate {
-            let iter = lock.global.values().chain(dep_libs.iter());
+            let iter = global.iter().chain(scope.iter());
 
             let reloc = |lib: ElfLibrary| {
                 log::debug!("Relocating dylib [{}]", lib.name());
-                let lazy_scope = create_lazy_scope(&dep_libs, lib.dylib.is_lazy());
+                let lazy_scope = create_lazy_scope(&scope, lib.dylib.is_lazy());
+                let _textrel = lib.textrel_guard();
+                let from = String::from(lib.name());
+                let find_strong = find_strong(&strong, unsafe { lib.dylib.core_component_ref() });
+                let find = |name: &str| {
+                    audit::symbind(name, &from)
+                        .or_else(|| interpose.and_then(|interpose| interpose(name)))
+                        .or_else(|| builtin::find(name))
+                        // STB_GLOBAL的定义优先于STB_WEAK的定义
+                        .or_else(|| find_strong(name))
+                };
                 lib.dylib
-                    .relocate(
-                        iter,
-                        &|name| builtin::BUILTIN.get(name).copied(),
-                        deal_unknown,
-                        lazy_scope,
-                    )
-                    .map(|lib| lib.into_core_component())
+                    .relocate(iter, &find, deal_unknown, lazy_scope)
+                    .map(|lib| {
+                        unsafe { symbolic::add_addends(lib.core_component()) };
+                        emit(LoadEvent::Relocated { name: lib.name() })
+                    })
             };
+            // 新加载的库还没有被注册和初始化，返回时就会被释放
             reloc(core::mem::take(&mut new_libs[item.idx]).unwrap())?;
+            order.push(new_pos[item.idx]);
         }
     }
 
     let deps = Arc::new(dep_libs.into_boxed_slice());
-    recycler.is_recycler = false;
     let core = deps[0].clone();
 
+    let mut lock = manager.write();
+    // 其他线程可能在此期间加载了同一个库，此时使用先注册的库，丢弃新加载的库
+    if let Some(lib) = lock
+        .all
+        .get(registry_name(&core))
+        .filter(|lib| is_new && lib.deps().is_some())
+    {
+        log::debug!(
+            "[{}] has been loaded by another thread, discard the new one",
+            core.shortname()
+        );
+        let res = lib.get_dylib(manager);
+        drop(lock);
+        wait_init(&res);
+        return Ok(Some(res));
+    }
+    // 新库已经绑定到了新加载的依赖库上，不能换成先注册的库，只能丢弃所有新库后重新加载，
+    // 否则同一个库会有两个实例
+    if new_pos
+        .iter()
+        .skip(1)
+        .any(|pos| lock.all.contains_key(registry_name(&deps[*pos])))
+    {
+        return Ok(None);
+    }
+    for pos in new_pos.iter().skip(1) {
+        register(deps[*pos].clone(), flags, None, &mut lock);
+    }
+    for name in promoted {
+        if let Some(lib) = lock.all.get_mut(&name) {
+            log::debug!(
+                "Trying to update a library. Name: [{}] Old flags:[{:?}] New flags:[{:?}]",
+                name,
+                lib.flags(),
+                flags
+            );
+            lib.set_flags(flags);
+            let core = lib.core_component();
+            lock.global.insert(name, core);
+        }
+    }
+
     let res = Dylib {
         inner: core.clone(),
         flags,
         deps: Some(deps.clone()),
+        manager,
         _marker: PhantomData,
     };
     //重新注册因为更新了deps
-    register(core, flags, Some(deps), &mut lock, false, None);
-    Ok(res)
+    register(core, flags, Some(deps.clone()), &mut lock);
+    // 在其他线程能使用新库之前认领它们的init函数，其他线程会等待init函数返回
+    let claimed: Vec<usize> = order
+        .into_iter()
+        .filter(|pos| deferred::claim(&deps[*pos], flags))
+        .collect();
+    drop(lock);
+    for pos in claimed {
+        deferred::run(&deps[pos]);
+    }
+    // 依赖的旧库可能正在被其他线程初始化
+    for (pos, dep) in deps.iter().enumerate() {
+        if !new_pos.contains(&pos) {
+            deferred::wait(dep);
+        }
+    }
+    if is_new {
+        audit::objopen(&res);
+    }
+    Ok(Some(res))
 }
 
 #[cfg(feature = "std")]
 pub mod imp {
     use super::MANAGER;
     use crate::{
-        find_lib_error, init::OLD_DL_ITERATE_PHDR, loader::find_symbol, ElfLibrary, OpenFlags,
-        Result,
+        event::{emit, LoadEvent},
+        find_lib_error,
+        init::OLD_DL_ITERATE_PHDR,
+        loader::{find_symbol_bytes, phdrs, symkind},
+        register::{namespaces, LM_ID_BASE, SYSTEM_LIB_COUNT},
+        ElfLibrary, OpenFlags, Result,
     };
     use core::{
         ffi::{c_char, c_int, c_void, CStr},
         mem::forget,
-        ptr::null,
+        ptr::{null, null_mut},
         str::FromStr,
+        sync::atomic::{AtomicBool, Ordering},
     };
     use dynamic_loader_cache::{Cache as LdCache, Result as LdResult};
-    use elf_loader::CoreComponent;
+    use elf_loader::{abi::PT_LOAD, CoreComponent};
     use libc::dl_phdr_info;
     use spin::Lazy;
-    use std::{path::PathBuf, sync::Arc};
+    use std::{
+        path::{Path, PathBuf},
+        sync::Arc,
+    };
 
     static LD_LIBRARY_PATH: Lazy<Box<[PathBuf]>> = Lazy::new(|| {
         let library_path = std::env::var("LD_LIBRARY_PATH").unwrap_or(String::new());
@@ -289,6 +684,10 @@ pub mod imp {
         ]
         .into_boxed_slice()
     });
+    // 设置了DLOPEN_RS_NO_LDCACHE时不读取/etc/ld.so.cache
+    static LD_CACHE_ENABLED: Lazy<AtomicBool> = Lazy::new(|| {
+        AtomicBool::new(std::env::var_os("DLOPEN_RS_NO_LDCACHE").is_none_or(|val| val.is_empty()))
+    });
     static LD_CACHE: Lazy<Box<[PathBuf]>> = Lazy::new(|| {
         build_ld_cache().unwrap_or_else(|err| {
             log::warn!("Build ld cache failed: {}", err);
@@ -296,6 +695,32 @@ pub mod imp {
         })
     });
 
+    /// Enable or disable searching the directories listed in `/etc/ld.so.cache` when looking for dependencies.
+    ///
+    /// It is enabled by default, unless the environment variable `DLOPEN_RS_NO_LDCACHE` is set to a nonempty string.
+    /// When it is disabled, the dependencies are only searched in `LD_LIBRARY_PATH`, `DT_RPATH`/`DT_RUNPATH`
+    /// and `/lib:/usr/lib`, and `/etc/ld.so.cache` is never read.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dlopen_rs::{ElfLibrary, OpenFlags};
+    ///
+    /// dlopen_rs::set_ld_cache_enabled(false);
+    /// let lib = ElfLibrary::dlopen("/path/to/libplugin.so", OpenFlags::RTLD_NOW).unwrap();
+    /// ```
+    pub fn set_ld_cache_enabled(enable: bool) {
+        LD_CACHE_ENABLED.store(enable, Ordering::Release);
+    }
+
+    #[inline]
+    fn ld_cache() -> &'static [PathBuf] {
+        if LD_CACHE_ENABLED.load(Ordering::Acquire) {
+            &LD_CACHE
+        } else {
+            &[]
+        }
+    }
+
     #[inline]
     fn build_ld_cache() -> LdResult<Box<[PathBuf]>> {
         use std::collections::HashSet;
@@ -317,8 +742,14 @@ pub mod imp {
         if !rpath.contains('$') {
             return deal_path(rpath);
         }
+        const TOKENS: [&str; 3] = ["ORIGIN", "LIB", "PLATFORM"];
         for s in rpath.split('$').skip(1) {
-            if !s.starts_with("ORIGIN") && !s.starts_with("{ORIGIN}") {
+            if !TOKENS.iter().any(|token| {
+                s.strip_prefix(token).is_some()
+                    || s.strip_prefix('{')
+                        .and_then(|s| s.strip_prefix(token))
+                        .is_some_and(|s| s.starts_with('}'))
+            }) {
                 log::warn!("DT_RUNPATH format is incorrect: [{}]", rpath);
                 return Box::new([]);
             }
@@ -328,7 +759,19 @@ pub mod imp {
         } else {
             "."
         };
-        deal_path(&rpath.to_string().replace("$ORIGIN", dir))
+        let lib = if cfg!(target_pointer_width = "64") {
+            "lib64"
+        } else {
+            "lib"
+        };
+        let platform = std::env::consts::ARCH;
+        let mut rpath = rpath.to_string();
+        for (token, value) in TOKENS.iter().zip([dir, lib, platform]) {
+            rpath = rpath
+                .replace(&format!("${{{}}}", token), value)
+                .replace(&format!("${}", token), value);
+        }
+        deal_path(&rpath)
     }
 
     #[inline]
@@ -338,24 +781,87 @@ pub mod imp {
             .collect()
     }
 
+    /// Where to load a dependency from, returned by the function set with [`set_dependency_resolver`].
+    #[derive(Debug)]
+    pub enum DepSource {
+        /// Load the dependency from bytes, like `ElfLibrary::dlopen_from_binary`.
+        Bytes(Vec<u8>),
+        /// Load the dependency from the file at the path.
+        Path(PathBuf),
+        /// Do not load the dependency. Its symbols must be provided by other libraries or builtin symbols.
+        Skip,
+    }
+
+    type DependencyResolver = Box<dyn Fn(&str) -> Option<DepSource> + Send + Sync>;
+
+    static DEPENDENCY_RESOLVER: spin::RwLock<Option<DependencyResolver>> = spin::RwLock::new(None);
+
+    /// Set a function that decides where each dependency (a name in `DT_NEEDED`) is loaded from.
+    /// It replaces the previously set function.
+    ///
+    /// It is called before the default search of `LD_LIBRARY_PATH`, `DT_RPATH`/`DT_RUNPATH` and the others,
+    /// and the default search is used when it returns `None`. It is not called for the libraries that have already been loaded.
+    ///
+    /// # Note
+    /// The function must not call `set_dependency_resolver` or `clear_dependency_resolver`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// use dlopen_rs::{DepSource, ElfLibrary, OpenFlags};
+    ///
+    /// static LIBFOO: &[u8] = &[];
+    /// dlopen_rs::set_dependency_resolver(|name| match name {
+    ///     "libfoo.so" => Some(DepSource::Bytes(LIBFOO.to_vec())),
+    ///     "libbar.so.1" => Some(DepSource::Path("/opt/bar/libbar.so.1".into())),
+    ///     _ => None,
+    /// });
+    /// let lib = ElfLibrary::dlopen("/path/to/libplugin.so", OpenFlags::RTLD_NOW).unwrap();
+    /// ```
+    pub fn set_dependency_resolver(
+        resolver: impl Fn(&str) -> Option<DepSource> + Send + Sync + 'static,
+    ) {
+        *DEPENDENCY_RESOLVER.write() = Some(Box::new(resolver));
+    }
+
+    /// Remove the function set by [`set_dependency_resolver`].
+    pub fn clear_dependency_resolver() {
+        *DEPENDENCY_RESOLVER.write() = None;
+    }
+
+    #[inline]
+    pub(crate) fn resolve_dependency(lib_name: &str) -> Option<DepSource> {
+        DEPENDENCY_RESOLVER
+            .read()
+            .as_ref()
+            .and_then(|resolver| resolver(lib_name))
+    }
+
     #[inline]
     pub(crate) fn find_library(
+        search_paths: &[String],
         cur_rpath: usize,
         rpath_vec: &mut Vec<Box<[PathBuf]>>,
         lib_name: &str,
         mut f: impl FnMut(std::fs::File, std::path::PathBuf, &mut Vec<Box<[PathBuf]>>) -> Result<()>,
     ) -> Result<()> {
-        // Search order: DT_RPATH(deprecated) -> LD_LIBRARY_PATH -> DT_RUNPATH -> /etc/ld.so.cache -> /lib:/usr/lib.
-        let search_paths = LD_LIBRARY_PATH
-            .iter()
-            .chain(rpath_vec[cur_rpath].iter())
-            .chain(LD_CACHE.iter())
-            .chain(DEFAULT_PATH.iter());
+        // Search order: search paths of LoadBuilder -> DT_RPATH(deprecated) -> LD_LIBRARY_PATH -> DT_RUNPATH -> /etc/ld.so.cache -> /lib:/usr/lib.
+        let search_paths = search_paths.iter().map(Path::new).chain(
+            LD_LIBRARY_PATH
+                .iter()
+                .chain(rpath_vec[cur_rpath].iter())
+                .chain(ld_cache().iter())
+                .chain(DEFAULT_PATH.iter())
+                .map(PathBuf::as_path),
+        );
 
         for path in search_paths {
             let file_path = path.join(lib_name);
             log::trace!("Try to open dependency shared object: [{:?}]", file_path);
             if let Ok(file) = std::fs::File::open(&file_path) {
+                emit(LoadEvent::DependencyResolved {
+                    name: lib_name,
+                    path: file_path.to_str().unwrap(),
+                });
                 f(file, file_path, rpath_vec)?;
                 return Ok(());
             }
@@ -374,30 +880,42 @@ pub mod imp {
         >,
         data: *mut libc::c_void,
     ) -> libc::c_int {
-        let reader = MANAGER.read();
-        let mut ret = OLD_DL_ITERATE_PHDR.unwrap()(callback, data);
-        if ret != 0 {
-            return ret;
-        }
-        for lib in reader.all.values() {
-            let phdrs = lib.core_component_ref().phdrs();
-            if phdrs.is_empty() {
-                continue;
+        let mut ret = 0;
+        if let Some(old_dl_iterate_phdr) = OLD_DL_ITERATE_PHDR {
+            ret = old_dl_iterate_phdr(callback, data);
+            if ret != 0 {
+                return ret;
             }
-            let mut info = dl_phdr_info {
-                dlpi_addr: lib.core_component_ref().base() as _,
-                dlpi_name: lib.core_component_ref().cname().as_ptr(),
-                dlpi_phdr: phdrs.as_ptr().cast(),
-                dlpi_phnum: phdrs.len() as _,
-                dlpi_adds: reader.all.len() as _,
-                dlpi_subs: 0,
-                dlpi_tls_modid: 0,
-                dlpi_tls_data: core::ptr::null_mut(),
+        }
+        let system_libs = SYSTEM_LIB_COUNT.load(Ordering::Acquire);
+        for manager in namespaces() {
+            let reader = manager.read();
+            // 其他命名空间中的系统库和基础命名空间中的是同一份
+            let skip = if reader.nsid == LM_ID_BASE {
+                0
+            } else {
+                system_libs
             };
-            if let Some(callback) = callback {
-                ret = callback(&mut info, size_of::<dl_phdr_info>(), data);
-                if ret != 0 {
-                    break;
+            for lib in reader.all.values().skip(skip) {
+                let phdrs = phdrs(lib.core_component_ref());
+                if phdrs.is_empty() {
+                    continue;
+                }
+                let mut info = dl_phdr_info {
+                    dlpi_addr: lib.core_component_ref().base() as _,
+                    dlpi_name: lib.core_component_ref().cname().as_ptr(),
+                    dlpi_phdr: phdrs.as_ptr().cast(),
+                    dlpi_phnum: phdrs.len() as _,
+                    dlpi_adds: reader.all.len() as _,
+                    dlpi_subs: 0,
+                    dlpi_tls_modid: 0,
+                    dlpi_tls_data: core::ptr::null_mut(),
+                };
+                if let Some(callback) = callback {
+                    ret = callback(&mut info, size_of::<dl_phdr_info>(), data);
+                    if ret != 0 {
+                        return ret;
+                    }
                 }
             }
         }
@@ -405,58 +923,248 @@ pub mod imp {
     }
 
     /// It is the same as `dlopen`.
+    ///
+    /// If `filename` is null, it returns a handle of the global scope: `dlsym` with this handle searches
+    /// the program, the libraries loaded with it and the libraries opened with `RTLD_GLOBAL`.
     pub unsafe fn dlopen(filename: *const c_char, flags: c_int) -> *const c_void {
-        let mut lib = if filename.is_null() {
-            MANAGER.read().all.get_index(0).unwrap().1.get_dylib()
-        } else {
-            let flags = OpenFlags::from_bits_retain(flags as _);
-            let filename = core::ffi::CStr::from_ptr(filename);
-            let path = filename.to_str().unwrap();
-            if let Ok(lib) = ElfLibrary::dlopen(path, flags) {
-                lib
-            } else {
-                return null();
-            }
+        if filename.is_null() {
+            log::info!("dlopen: Open the global scope");
+            return global_handle();
+        }
+        let flags = OpenFlags::from_bits_retain(flags as _);
+        let filename = core::ffi::CStr::from_ptr(filename);
+        let path = filename.to_str().unwrap();
+        let Ok(mut lib) = ElfLibrary::dlopen(path, flags) else {
+            return null();
         };
         Arc::into_raw(core::mem::take(&mut lib.deps).unwrap()) as _
     }
 
-    /// It is the same as `dlsym`.
-    pub unsafe fn dlsym(handle: *const c_void, symbol_name: *const c_char) -> *const c_void {
+    // dlopen(NULL)返回的句柄，用它查找符号时会搜索全局作用域
+    static GLOBAL_HANDLE: u8 = 0;
+
+    #[inline]
+    fn global_handle() -> *const c_void {
+        &GLOBAL_HANDLE as *const u8 as _
+    }
+
+    // 裸函数的函数体只能是一个naked_asm!，所以用宏为每个架构生成dlsym
+    macro_rules! dlsym {
+        ($($asm:tt)*) => {
+            /// It is the same as `dlsym`.
+            ///
+            /// `handle` can also be `RTLD_DEFAULT` (0), which searches the global scope, or `RTLD_NEXT` (-1), which searches
+            /// the libraries loaded after the caller's library like [`dlsym_next`]. The caller is identified by the return address,
+            /// so `dlsym` must be called directly from the library rather than through a wrapper function.
+            ///
+            /// # Safety
+            /// `handle` must be `RTLD_DEFAULT`, `RTLD_NEXT` or a handle returned by `dlopen`, and `symbol_name` must be a valid
+            /// null-terminated string.
+            #[unsafe(naked)]
+            pub unsafe extern "C" fn dlsym(
+                handle: *const c_void,
+                symbol_name: *const c_char,
+            ) -> *const c_void {
+                core::arch::naked_asm!($($asm)*)
+            }
+        };
+    }
+
+    // 将返回地址作为第三个参数传给dlsym_impl
+    #[cfg(target_arch = "x86_64")]
+    dlsym!("mov rdx, [rsp]", "jmp {}", sym dlsym_impl);
+    #[cfg(target_arch = "aarch64")]
+    dlsym!("mov x2, x30", "b {}", sym dlsym_impl);
+    #[cfg(target_arch = "riscv64")]
+    dlsym!("mv a2, ra", "tail {}", sym dlsym_impl);
+
+    unsafe extern "C" fn dlsym_impl(
+        handle: *const c_void,
+        symbol_name: *const c_char,
+        caller: *const c_void,
+    ) -> *const c_void {
         const RTLD_DEFAULT: usize = 0;
         const RTLD_NEXT: usize = usize::MAX;
         let value = handle as usize;
-        let name = CStr::from_ptr(symbol_name).to_str().unwrap_unchecked();
-        let sym = if value == RTLD_DEFAULT {
-            log::info!("dlsym: Use RTLD_DEFAULT flag to find symbol [{}]", name);
-            MANAGER
-                .read()
-                .global
-                .values()
-                .find_map(|lib| lib.get::<()>(name).map(|v| v.into_raw()))
+        // 符号名来自C调用者，不一定是合法的UTF-8
+        let name = CStr::from_ptr(symbol_name).to_bytes();
+        let sym = if value == RTLD_DEFAULT || handle == global_handle() {
+            log::info!(
+                "dlsym: Find symbol [{}] in the global scope",
+                name.escape_ascii()
+            );
+            let reader = MANAGER.read();
+            find_symbol_bytes(reader.global.values(), name)
         } else if value == RTLD_NEXT {
-            todo!("RTLD_NEXT is not supported")
+            // 和dladdr一样，通过返回地址找到调用者所在的库
+            return dlsym_next(caller, symbol_name);
         } else {
             let libs = Arc::from_raw(handle as *const Box<[CoreComponent]>);
-            let symbol = find_symbol::<()>(&libs, name)
-                .ok()
-                .map(|sym| sym.into_raw());
+            log::info!(
+                "dlsym: Find symbol [{}] in [{}]",
+                name.escape_ascii(),
+                libs[0].shortname()
+            );
+            let symbol = find_symbol_bytes(libs.iter(), name);
             forget(libs);
             symbol
         };
         sym.unwrap_or(null()).cast()
     }
 
+    #[inline]
+    fn contains(lib: &CoreComponent, addr: usize) -> bool {
+        phdrs(lib)
+            .iter()
+            .filter(|phdr| phdr.p_type == PT_LOAD)
+            .any(|phdr| {
+                let start = lib.base() + phdr.p_vaddr as usize;
+                (start..start + phdr.p_memsz as usize).contains(&addr)
+            })
+    }
+
+    /// It is the same as `dladdr`.
+    ///
+    /// Only the libraries managed by dlopen-rs are searched, and the symbol is looked up in their `.dynsym`.
+    ///
+    /// # Safety
+    /// `info` must be valid for writes.
+    pub unsafe fn dladdr(addr: *const c_void, info: *mut libc::Dl_info) -> c_int {
+        dladdr1(addr, info, null_mut(), 0)
+    }
+
+    /// It is the same as `dladdr1`.
+    ///
+    /// `flags` can be `RTLD_DL_SYMENT`, which stores the pointer to the `Elf64_Sym` of the symbol in `extra_info`,
+    /// or `RTLD_DL_LINKMAP`, which stores the pointer to the `link_map` of the library. The `link_map` is only
+    /// available for the libraries loaded with the `debug` feature, otherwise it fails.
+    ///
+    /// # Safety
+    /// `info` must be valid for writes, and so must `extra_info` if `flags` is not 0.
+    pub unsafe fn dladdr1(
+        addr: *const c_void,
+        info: *mut libc::Dl_info,
+        extra_info: *mut *mut c_void,
+        flags: c_int,
+    ) -> c_int {
+        const RTLD_DL_SYMENT: c_int = 1;
+        const RTLD_DL_LINKMAP: c_int = 2;
+        let addr = addr as usize;
+        let Some(lib) = namespaces().into_iter().find_map(|manager| {
+            manager
+                .read()
+                .all
+                .values()
+                .map(|lib| lib.core_component_ref())
+                .find(|lib| contains(lib, addr))
+                .cloned()
+        }) else {
+            log::warn!("dladdr: can not find the library containing [0x{:x}]", addr);
+            return 0;
+        };
+        let info = &mut *info;
+        info.dli_fname = lib.cname().as_ptr();
+        info.dli_fbase = lib.base() as _;
+        info.dli_sname = null();
+        info.dli_saddr = null_mut();
+        let sym = symkind::nearest(lib.base(), phdrs(&lib), addr);
+        if let Some((name, start, _)) = sym {
+            info.dli_sname = name.as_ptr();
+            info.dli_saddr = start as _;
+        }
+        match flags {
+            RTLD_DL_SYMENT => {
+                *extra_info = sym.map_or(null_mut(), |(_, _, entry)| entry as _);
+            }
+            RTLD_DL_LINKMAP => {
+                #[cfg(feature = "debug")]
+                let link_map = lib
+                    .user_data()
+                    .get(crate::loader::DEBUG_INFO_ID)
+                    .and_then(|data| data.downcast_ref::<crate::debug::DebugInfo>())
+                    .map(|debug| debug.link_map());
+                #[cfg(not(feature = "debug"))]
+                let link_map: Option<*const c_void> = None;
+                let Some(link_map) = link_map else {
+                    log::warn!("dladdr1: [{}] has no link_map", lib.shortname());
+                    return 0;
+                };
+                *extra_info = link_map as _;
+            }
+            _ => {}
+        }
+        1
+    }
+
+    /// It is the same as `dlsym(RTLD_NEXT, symbol_name)` called from the library containing the address `caller`.
+    ///
+    /// It finds the next occurrence of the symbol in the libraries loaded after the caller's library,
+    /// which is what interposition shims use to call the function they wrap. `caller` is usually the address
+    /// of a function in the shim itself.
+    ///
+    /// # Safety
+    /// `symbol_name` must be a valid null-terminated string.
+    pub unsafe fn dlsym_next(caller: *const c_void, symbol_name: *const c_char) -> *const c_void {
+        let name = CStr::from_ptr(symbol_name).to_bytes();
+        let addr = caller as usize;
+        let reader = MANAGER.read();
+        let Some(idx) = reader
+            .all
+            .values()
+            .position(|lib| contains(lib.core_component_ref(), addr))
+        else {
+            log::warn!(
+                "dlsym_next: can not find the library containing [0x{:x}]",
+                addr
+            );
+            return null();
+        };
+        log::info!(
+            "dlsym_next: Find symbol [{}] after [{}]",
+            name.escape_ascii(),
+            reader.all[idx].core_component_ref().shortname()
+        );
+        find_symbol_bytes(
+            reader
+                .all
+                .values()
+                .skip(idx + 1)
+                .map(|lib| lib.core_component_ref()),
+            name,
+        )
+        .unwrap_or(null())
+        .cast()
+    }
+
     /// It is the same as `dlclose`.
+    ///
+    /// It returns 0 on success, and nonzero if `handle` is null or is not a handle returned by `dlopen`.
+    /// The library may stay resident after it is closed, see `set_unload_veto`.
     pub unsafe fn dlclose(handle: *const c_void) -> c_int {
-        let deps = Arc::from_raw(handle as *const Box<[CoreComponent]>);
-        let dylib = MANAGER
-            .read()
-            .all
-            .get(deps[0].shortname())
-            .unwrap()
-            .get_dylib();
-        drop(deps);
+        if handle == global_handle() {
+            return 0;
+        }
+        if handle.is_null() {
+            log::error!("dlclose: invalid handle [null]");
+            return 1;
+        }
+        // 先确认句柄是某个已注册的库的依赖数组，再通过它还原Arc，否则无效的句柄会导致未定义行为
+        let Some(dylib) = namespaces().into_iter().find_map(|manager| {
+            manager
+                .read()
+                .all
+                .values()
+                .find(|lib| {
+                    lib.deps()
+                        .is_some_and(|deps| Arc::as_ptr(deps) as *const c_void == handle)
+                })
+                .map(|lib| lib.get_dylib(manager))
+        }) else {
+            log::error!("dlclose: invalid handle [{:p}]", handle);
+            return 1;
+        };
+        // 释放dlopen返回句柄时持有的引用
+        drop(Arc::from_raw(handle as *const Box<[CoreComponent]>));
         log::info!("dlclose: Closing [{}]", dylib.name());
         0
     }
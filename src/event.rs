@@ -0,0 +1,45 @@
+use alloc::boxed::Box;
+use spin::RwLock;
+
+/// An event emitted while loading and unloading dynamic libraries, see [`set_event_sink`].
+#[derive(Clone, Copy, Debug)]
+pub enum LoadEvent<'a> {
+    /// A dynamic library has been mapped into memory at `addr`.
+    Loading { name: &'a str, addr: usize },
+    /// A dynamic library has been relocated.
+    Relocated { name: &'a str },
+    /// A dependency `name` has been found at `path`.
+    DependencyResolved { name: &'a str, path: &'a str },
+    /// A dynamic library has been destroyed.
+    Destroyed { name: &'a str },
+}
+
+type EventSink = Box<dyn for<'a> Fn(LoadEvent<'a>) + Send + Sync>;
+
+static EVENT_SINK: RwLock<Option<EventSink>> = RwLock::new(None);
+
+/// Set a function that receives a [`LoadEvent`] at each point where dlopen-rs logs the loading progress.
+/// It replaces the previously set function. The `log` messages are still emitted.
+///
+/// # Note
+/// The function is called while dlopen-rs may hold its internal locks, so it must not load or unload libraries.
+///
+/// # Examples
+/// ```
+/// dlopen_rs::set_event_sink(|event| println!("{:?}", event));
+/// ```
+pub fn set_event_sink(sink: impl for<'a> Fn(LoadEvent<'a>) + Send + Sync + 'static) {
+    *EVENT_SINK.write() = Some(Box::new(sink));
+}
+
+/// Remove the function set by [`set_event_sink`].
+pub fn clear_event_sink() {
+    *EVENT_SINK.write() = None;
+}
+
+#[inline]
+pub(crate) fn emit(event: LoadEvent) {
+    if let Some(sink) = EVENT_SINK.read().as_ref() {
+        sink(event);
+    }
+}
@@ -1,11 +1,14 @@
 use crate::{
-    register::{global_find, register, MANAGER},
+    find_symbol_error,
+    loader::{deferred, dynflags},
+    register::{global_find, namespaces, register, MANAGER, SYSTEM_LIB_COUNT},
     Dylib, OpenFlags, Result,
 };
 use core::{
     ffi::{c_char, c_int, c_void, CStr},
     marker::PhantomData,
     ptr::{addr_of, addr_of_mut, null_mut, NonNull},
+    sync::atomic::{AtomicBool, Ordering},
 };
 use elf_loader::{
     arch::Dyn, dynamic::ElfRawDynamic, segment::ElfSegments, set_global_scope, RelocatedDylib,
@@ -65,6 +68,7 @@ pub(crate) static mut OLD_DL_ITERATE_PHDR: Option<
 > = None;
 
 static ONCE: Once = Once::new();
+static FINI_AT_EXIT: AtomicBool = AtomicBool::new(false);
 static mut PROGRAM_NAME: Option<PathBuf> = None;
 
 pub(crate) unsafe fn from_link_map(link_map: &LinkMap) -> Result<Option<Dylib>> {
@@ -137,7 +141,8 @@ pub(crate) unsafe fn from_link_map(link_map: &LinkMap) -> Result<Option<Dylib>>
         CStr::from_ptr(link_map.l_name).to_owned(),
         link_map.l_addr as usize,
         dynamic,
-        &[],
+        // 系统动态链接器加载的库的第一个段从文件开头映射，elf头部就在l_addr处
+        dynflags::mapped_phdrs(memory.as_ptr() as usize),
         segments,
         user_data,
     );
@@ -150,57 +155,117 @@ pub(crate) unsafe fn from_link_map(link_map: &LinkMap) -> Result<Option<Dylib>>
         inner: core.clone(),
         flags,
         deps: deps.clone(),
+        manager: &MANAGER,
         _marker: PhantomData,
     };
 
-    register(core, flags, deps, &mut MANAGER.write(), false, None);
+    register(core, flags, deps, &mut MANAGER.write());
     Ok(Some(dylib))
 }
 
+/// Run the fini functions of the libraries opened with `RTLD_NODELETE` when the process exits, in reverse load order.
+/// It is disabled by default. The exit hook is installed by [`init`], so it has no effect if `init` is not called.
+pub fn set_fini_at_exit(enable: bool) {
+    FINI_AT_EXIT.store(enable, Ordering::Release);
+}
+
+extern "C" fn fini_at_exit() {
+    if !FINI_AT_EXIT.load(Ordering::Acquire) {
+        return;
+    }
+    let system_libs = SYSTEM_LIB_COUNT.load(Ordering::Acquire);
+    for manager in namespaces().iter().rev() {
+        let reader = manager.read();
+        // 系统库的fini函数由ld.so调用
+        for lib in reader.all.values().skip(system_libs).rev() {
+            let core = lib.core_component_ref();
+            if !lib.flags().contains(OpenFlags::RTLD_NODELETE)
+                || lib.deps().is_none()
+                || deferred::get(core).is_some_and(|init| init.is_pending())
+            {
+                continue;
+            }
+            log::debug!(
+                "Call the fini function from the dylib [{}] at exit",
+                core.shortname()
+            );
+            unsafe { core.call_fini() };
+        }
+    }
+}
+
 /// `init` is responsible for the initialization of dlopen_rs, If you want to use the dynamic library that the program itself depends on,
 /// or want to use the debug function, please call it at the beginning. This is usually necessary.
+///
+/// Errors during the initialization are logged as warnings, use [`try_init`] to handle them.
 pub fn init() {
-    ONCE.call_once(|| {
-        let program_self = env::current_exe().unwrap();
-        unsafe { PROGRAM_NAME = Some(program_self) };
-        let debug = get_debug_struct();
-        let mut cur_map_ptr = debug.map;
-        debug.map = null_mut();
-        #[cfg(feature = "debug")]
-        {
-            let mut custom = crate::debug::DEBUG.lock().unwrap();
-            custom.debug = debug;
-            custom.tail = null_mut();
-            drop(custom);
-        }
-        while !cur_map_ptr.is_null() {
-            let cur_map = unsafe { &*cur_map_ptr };
-            unsafe { from_link_map(cur_map).unwrap() }.map(|lib| {
-                if lib.name().contains("libc.so") {
-                    unsafe {
-                        OLD_DL_ITERATE_PHDR = Some(core::mem::transmute(
-                            lib.get::<extern "C" fn(
-                                callback: Option<
-                                    unsafe extern "C" fn(
-                                        info: *mut libc::dl_phdr_info,
-                                        size: libc::size_t,
-                                        data: *mut c_void,
-                                    )
-                                        -> c_int,
-                                >,
+    if let Err(err) = try_init() {
+        log::warn!("Initialization is incomplete: {}", err);
+    }
+}
+
+/// The same as [`init`], but returns the error instead of logging it.
+///
+/// dlopen-rs is still usable when it returns an error: the existing libraries that could not be registered
+/// are not used to resolve symbols, which does not matter for libraries loaded with `CUSTOM_NOT_REGISTER`.
+/// The initialization is only done once, so subsequent calls return `Ok(())`.
+pub fn try_init() -> Result<()> {
+    let mut result = Ok(());
+    ONCE.call_once(|| result = init_impl());
+    result
+}
+
+fn init_impl() -> Result<()> {
+    let program_self = env::current_exe().unwrap_or_default();
+    unsafe { PROGRAM_NAME = Some(program_self) };
+    let debug = get_debug_struct();
+    let mut cur_map_ptr = debug.map;
+    debug.map = null_mut();
+    #[cfg(feature = "debug")]
+    {
+        let mut custom = crate::debug::DEBUG.lock().unwrap();
+        custom.debug = debug;
+        custom.tail = null_mut();
+        drop(custom);
+    }
+    let mut first_err = None;
+    while !cur_map_ptr.is_null() {
+        let cur_map = unsafe { &*cur_map_ptr };
+        match unsafe { from_link_map(cur_map) } {
+            Ok(Some(lib)) if lib.name().contains("libc.so") => {
+                if let Ok(sym) = unsafe {
+                    lib.get::<extern "C" fn(
+                        callback: Option<
+                            unsafe extern "C" fn(
+                                info: *mut libc::dl_phdr_info,
+                                size: libc::size_t,
                                 data: *mut c_void,
-                            ) -> c_int>("dl_iterate_phdr")
-                                .unwrap()
-                                .into_raw(),
-                        ))
-                    };
+                            ) -> c_int,
+                        >,
+                        data: *mut c_void,
+                    ) -> c_int>("dl_iterate_phdr")
+                } {
+                    unsafe { OLD_DL_ITERATE_PHDR = Some(*sym) };
                 }
-            });
-
-            cur_map_ptr = cur_map.l_next;
+            }
+            Ok(_) => {}
+            Err(err) => {
+                log::warn!("Failed to initialize an existing library: {}", err);
+                first_err.get_or_insert(err);
+            }
         }
+        cur_map_ptr = cur_map.l_next;
+    }
 
-        unsafe { set_global_scope(global_find as _) };
-        log::info!("Initialization is complete");
-    });
+    SYSTEM_LIB_COUNT.store(MANAGER.read().all.len(), Ordering::Release);
+    unsafe { set_global_scope(global_find as _) };
+    unsafe { libc::atexit(fini_at_exit) };
+    if let Some(err) = first_err {
+        return Err(err);
+    }
+    if unsafe { (*addr_of!(OLD_DL_ITERATE_PHDR)).is_none() } {
+        return Err(find_symbol_error("can not find libc's dl_iterate_phdr"));
+    }
+    log::info!("Initialization is complete");
+    Ok(())
 }
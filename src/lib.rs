@@ -28,9 +28,14 @@
 #![cfg_attr(not(feature = "std"), no_std)]
 extern crate alloc;
 
+#[cfg(feature = "std")]
+mod abort;
+mod audit;
+mod capabilities;
 #[cfg(feature = "debug")]
 mod debug;
 pub mod dlopen;
+mod event;
 #[cfg(feature = "std")]
 mod init;
 mod loader;
@@ -39,10 +44,46 @@ use alloc::string::{String, ToString};
 use bitflags::bitflags;
 use core::fmt::Display;
 
+#[cfg(feature = "std")]
+pub use abort::{catch_abort, install_abort_handler, PluginAbort};
+pub use audit::{set_auditor, Auditor};
+pub use capabilities::{capabilities, Capabilities, UnwindBackend};
+#[cfg(feature = "std")]
+pub use dlopen::imp::{
+    clear_dependency_resolver, set_dependency_resolver, set_ld_cache_enabled, DepSource,
+};
+pub use elf_loader::arch::ElfRela;
+/// A symbol from a dynamic library.
+///
+/// `Symbol::into_raw` gives the raw address of the symbol, e.g. to store it in a vtable or pass it to C.
+/// The address is only valid as long as the library that the symbol comes from is loaded.
 pub use elf_loader::Symbol;
+pub use event::{clear_event_sink, set_event_sink, LoadEvent};
+#[cfg(feature = "std")]
+pub use init::{init, set_fini_at_exit, try_init};
+#[cfg(feature = "compression")]
+pub use loader::compressed::Codec;
+#[cfg(not(feature = "std"))]
+pub use loader::staged::{register_binary, unregister_binary};
 #[cfg(feature = "std")]
-pub use init::init;
-pub use loader::{Dylib, ElfLibrary};
+pub use loader::LoadBuilder;
+#[cfg(feature = "sections")]
+pub use loader::Section;
+pub use loader::{
+    builtin::{register_builtin, register_host_symbols, unregister_builtin},
+    lazy::{set_lazy_failure_handler, LazyAction},
+    note::OsAbi,
+    property::CpuFeatures,
+    resolver::ExternResolver,
+    symkind::SymKind,
+    unknown::{set_unknown_reloc_policy, UnknownRelocHandler, UnknownRelocPolicy},
+    Dylib, ElfLibrary, OwnedSymbol,
+};
+pub use register::{
+    clear_unload_veto, loaded_libraries, set_unload_veto, Lmid, LM_ID_BASE, LM_ID_NEWLM,
+};
+#[cfg(feature = "std")]
+pub use register::{global_scope, with_global_scope, GlobalScope};
 
 #[cfg(not(any(
     target_arch = "x86_64",
@@ -60,6 +101,9 @@ bitflags! {
         const RTLD_LOCAL = 0;
         /// Perform lazy binding. Resolve symbols only as the code that references them is executed.
         /// If the symbol is never referenced, then it is never resolved.
+        /// The `.got.plt` of the library points to the resolver trampoline of `elf_loader`, which resolves
+        /// the symbol on the first call and writes the result back to the `.got.plt` slot.
+        /// It is ignored for libraries linked with `-z now` (`DF_BIND_NOW` or `DF_1_NOW`).
         const RTLD_LAZY = 1;
         /// If this value is specified, or the environment variable LD_BIND_NOW is set to a nonempty string,
         /// all undefined symbols in the shared object are resolved before dlopen() returns.
@@ -72,9 +116,19 @@ bitflags! {
         const RTLD_GLOBAL = 256;
         /// Do not unload the shared object during dlclose(). Consequently,
         /// the object's static and global variables are not reinitialized if the object is reloaded with dlopen() at a later time.
+        /// With `CUSTOM_NOT_REGISTER`, the library and its dependencies are intentionally leaked, so their memory stays mapped
+        /// after all handles are dropped, e.g. for libraries that install signal handlers or callbacks.
         const RTLD_NODELETE = 4096;
         /// dlopen-rs custom flag, true local loading, does not involve any global variable operations, no lock, and has the fastest loading speed.
         const CUSTOM_NOT_REGISTER = 1024;
+        /// dlopen-rs custom flag, do not call the init functions (`DT_INIT` and `DT_INIT_ARRAY`) during relocation.
+        /// They are called later by `Dylib::run_init`.
+        /// It also applies to the dependencies loaded together with the library, so no constructor of the new libraries runs
+        /// until `Dylib::run_init` is called, while the dependencies that had already been loaded are not affected.
+        const CUSTOM_DEFER_INIT = 2048;
+        /// dlopen-rs custom flag, do not register the unwind information (`PT_GNU_EH_FRAME`) of the library
+        /// with the unwinder, for hosts that manage their own unwinder. See `Dylib::eh_frame_range`.
+        const CUSTOM_SKIP_UNWIND_REGISTER = 8192;
     }
 }
 
@@ -90,6 +144,21 @@ pub enum Error {
     FindLibError { msg: String },
     /// Returned when failed to find a symbol.
     FindSymbolError { msg: String },
+    /// Returned when the file is an elf file without a dynamic section, so it is not a dynamic library.
+    NotADynamicLibrary { name: String },
+    /// Returned when a call into a library panicked, see `Dylib::call_catch`.
+    #[cfg(feature = "std")]
+    PanicError { msg: String },
+    /// Returned when failed to decompress a library.
+    #[cfg(feature = "compression")]
+    DecompressError { msg: String },
+    /// Returned when the digest of a library does not match the expected one.
+    #[cfg(feature = "verification")]
+    IntegrityError { msg: String },
+    /// Returned when the library has relocations against read-only segments (`DT_TEXTREL`),
+    /// which are refused by the `deny-textrel` feature.
+    #[cfg(feature = "deny-textrel")]
+    TextRelError { msg: String },
 }
 
 impl Display for Error {
@@ -100,6 +169,17 @@ impl Display for Error {
             Error::LoaderError { err } => write!(f, "{err}"),
             Error::FindLibError { msg } => write!(f, "{msg}"),
             Error::FindSymbolError { msg } => write!(f, "{msg}"),
+            Error::NotADynamicLibrary { name } => {
+                write!(f, "{name}: elf file does not have dynamic")
+            }
+            #[cfg(feature = "std")]
+            Error::PanicError { msg } => write!(f, "{msg}"),
+            #[cfg(feature = "compression")]
+            Error::DecompressError { msg } => write!(f, "{msg}"),
+            #[cfg(feature = "verification")]
+            Error::IntegrityError { msg } => write!(f, "{msg}"),
+            #[cfg(feature = "deny-textrel")]
+            Error::TextRelError { msg } => write!(f, "{msg}"),
         }
     }
 }
@@ -145,4 +225,40 @@ fn find_symbol_error(msg: impl ToString) -> Error {
     }
 }
 
+#[cfg(feature = "std")]
+#[cold]
+#[inline(never)]
+fn panic_error(msg: impl ToString) -> Error {
+    Error::PanicError {
+        msg: msg.to_string(),
+    }
+}
+
+#[cfg(feature = "compression")]
+#[cold]
+#[inline(never)]
+fn decompress_error(msg: impl ToString) -> Error {
+    Error::DecompressError {
+        msg: msg.to_string(),
+    }
+}
+
+#[cfg(feature = "verification")]
+#[cold]
+#[inline(never)]
+fn integrity_error(msg: impl ToString) -> Error {
+    Error::IntegrityError {
+        msg: msg.to_string(),
+    }
+}
+
+#[cfg(feature = "deny-textrel")]
+#[cold]
+#[inline(never)]
+fn textrel_error(msg: impl ToString) -> Error {
+    Error::TextRelError {
+        msg: msg.to_string(),
+    }
+}
+
 pub type Result<T> = core::result::Result<T, Error>;
@@ -0,0 +1,137 @@
+use crate::{dlopen::dlopen_impl, register::MANAGER, Dylib, ElfLibrary, OpenFlags, Result};
+use alloc::{string::String, sync::Arc, vec::Vec};
+use core::fmt::Debug;
+
+type Interpose = Arc<dyn Fn(&str) -> Option<*const ()> + Send + Sync>;
+
+/// Options for loading a dynamic library, created by [`ElfLibrary::builder`].
+///
+/// It carries the options that do not fit in `OpenFlags`. `ElfLibrary::dlopen` is the same as
+/// `ElfLibrary::builder().flags(flags).build_from_file(path)`.
+#[derive(Clone)]
+pub struct LoadBuilder {
+    flags: OpenFlags,
+    search_paths: Vec<String>,
+    interpose: Option<Interpose>,
+    #[cfg(feature = "verification")]
+    sha256: Option<[u8; 32]>,
+}
+
+impl Debug for LoadBuilder {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("LoadBuilder")
+            .field("flags", &self.flags)
+            .field("search_paths", &self.search_paths)
+            .field("interpose", &self.interpose.is_some())
+            .finish()
+    }
+}
+
+impl ElfLibrary {
+    /// Create a [`LoadBuilder`] with `OpenFlags::RTLD_LOCAL`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::builder()
+    ///     .flags(OpenFlags::RTLD_NOW)
+    ///     .search_paths(&["/opt/plugins/lib"])
+    ///     .defer_init(true)
+    ///     .build_from_file("/opt/plugins/libplugin.so")
+    ///     .unwrap();
+    /// lib.run_init();
+    /// ```
+    #[inline]
+    pub fn builder() -> LoadBuilder {
+        LoadBuilder {
+            flags: OpenFlags::RTLD_LOCAL,
+            search_paths: Vec::new(),
+            interpose: None,
+            #[cfg(feature = "verification")]
+            sha256: None,
+        }
+    }
+}
+
+impl LoadBuilder {
+    /// Set the flags used to load the library and its dependencies.
+    #[inline]
+    pub fn flags(mut self, flags: OpenFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Whether to defer the init functions until `Dylib::run_init` is called, see `OpenFlags::CUSTOM_DEFER_INIT`.
+    #[inline]
+    pub fn defer_init(mut self, defer: bool) -> Self {
+        self.flags.set(OpenFlags::CUSTOM_DEFER_INIT, defer);
+        self
+    }
+
+    /// Add directories that are searched for the dependencies before `DT_RPATH`, `LD_LIBRARY_PATH` and the others.
+    #[inline]
+    pub fn search_paths(mut self, paths: &[&str]) -> Self {
+        self.search_paths
+            .extend(paths.iter().map(|path| String::from(*path)));
+        self
+    }
+
+    /// Resolve symbols with `interpose` before the builtin symbols and the dependencies, when relocating
+    /// the library and the dependencies loaded with it. It does not affect other loads, unlike `ElfLibrary::preload`,
+    /// and the dependencies that have already been loaded are not relocated again.
+    ///
+    /// The libraries are loaded with `RTLD_NOW` instead of `RTLD_LAZY`, because lazily bound symbols are
+    /// looked up in the global scope first.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::ElfLibrary;
+    /// extern "C" fn fixed_time(_: *mut i64) -> i64 {
+    ///     0
+    /// }
+    ///
+    /// let lib = ElfLibrary::builder()
+    ///     .interpose(|name| (name == "time").then_some(fixed_time as *const ()))
+    ///     .build_from_file("/path/to/libplugin.so")
+    ///     .unwrap();
+    /// ```
+    #[inline]
+    pub fn interpose(
+        mut self,
+        interpose: impl Fn(&str) -> Option<*const ()> + Send + Sync + 'static,
+    ) -> Self {
+        self.interpose = Some(Arc::new(interpose));
+        self
+    }
+
+    /// Check that the sha256 digest of the library file is `digest` before loading it, see `ElfLibrary::from_file_verified`.
+    /// The dependencies are not checked.
+    #[cfg(feature = "verification")]
+    #[inline]
+    pub fn verify_sha256(mut self, digest: [u8; 32]) -> Self {
+        self.sha256 = Some(digest);
+        self
+    }
+
+    /// Load the library from `path` and its dependencies with these options. It is the same as dlopen.
+    pub fn build_from_file(self, path: impl AsRef<std::ffi::OsStr>) -> Result<Dylib<'static>> {
+        let path = path.as_ref().to_str().unwrap();
+        let mut flags = self.flags;
+        // 延迟绑定时先在全局作用域中查找符号，interpose不会生效
+        if self.interpose.is_some() {
+            flags.remove(OpenFlags::RTLD_LAZY);
+            flags.insert(OpenFlags::RTLD_NOW);
+        }
+        let interpose = self
+            .interpose
+            .as_deref()
+            .map(|interpose| interpose as &dyn Fn(&str) -> Option<*const ()>);
+        dlopen_impl(path, flags, &MANAGER, &self.search_paths, interpose, || {
+            #[cfg(feature = "verification")]
+            if let Some(digest) = &self.sha256 {
+                return ElfLibrary::from_file_verified(path, digest, flags);
+            }
+            ElfLibrary::from_file(path, flags)
+        })
+    }
+}
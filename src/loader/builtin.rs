@@ -1,7 +1,10 @@
 use super::tls::tls_get_addr;
 #[cfg(feature = "std")]
 use crate::dlopen::dl_iterate_phdr;
+use alloc::string::{String, ToString};
 use core::ffi::c_int;
+use indexmap::IndexMap;
+use spin::{Lazy, RwLock};
 #[cfg(not(feature = "std"))]
 fn dl_iterate_phdr() {}
 
@@ -53,3 +56,54 @@ pub(crate) const BUILTIN: phf::Map<&'static str, *const ()> = phf::phf_map!(
     "_Unwind_RaiseException" => unwinding::abi::_Unwind_RaiseException as _,
     "_Unwind_Resume_or_Rethrow" => unwinding::abi::_Unwind_Resume_or_Rethrow as _,
 );
+
+/// 用户注册的内置符号，优先级高于`BUILTIN`
+static CUSTOM_BUILTIN: Lazy<RwLock<IndexMap<String, usize>>> =
+    Lazy::new(|| RwLock::new(IndexMap::new()));
+
+/// Register a builtin symbol. During relocation, the symbols registered by this function are used
+/// before the builtin symbols provided by dlopen-rs and the symbols in the dependent libraries.
+/// Registering a symbol with an existing name replaces the old one.
+///
+/// # Safety
+/// `ptr` must point to a function or variable whose type matches what the loaded libraries expect.
+pub unsafe fn register_builtin(name: &str, ptr: *const ()) {
+    log::debug!("Register a builtin symbol [{}]", name);
+    CUSTOM_BUILTIN
+        .write()
+        .insert(name.to_string(), ptr as usize);
+}
+
+/// Export the API of the host to the libraries loaded afterwards, by registering each `(name, ptr)` pair
+/// with [`register_builtin`]. It is useful in `no_std`, where the symbols of the program itself can not be found by `init`.
+///
+/// # Safety
+/// Each pointer is used as-is as the address of the symbol, so it must follow the ABI that the libraries expect:
+/// a function must be `extern "C"` with the signature declared by the library, and a variable must have
+/// the layout declared by the library. The pointers must remain valid as long as any library using them is loaded.
+///
+/// # Examples
+/// ```no_run
+/// extern "C" fn host_log(msg: *const core::ffi::c_char) {}
+///
+/// unsafe { dlopen_rs::register_host_symbols(&[("host_log", host_log as *const ())]) };
+/// ```
+pub unsafe fn register_host_symbols(symbols: &[(&str, *const ())]) {
+    for (name, ptr) in symbols {
+        register_builtin(name, *ptr);
+    }
+}
+
+/// Remove a builtin symbol registered by [`register_builtin`]. The libraries that have already been
+/// relocated are not affected.
+pub fn unregister_builtin(name: &str) -> bool {
+    CUSTOM_BUILTIN.write().shift_remove(name).is_some()
+}
+
+#[inline]
+pub(crate) fn find(name: &str) -> Option<*const ()> {
+    if let Some(ptr) = CUSTOM_BUILTIN.read().get(name) {
+        return Some(*ptr as _);
+    }
+    BUILTIN.get(name).copied()
+}
@@ -0,0 +1,222 @@
+#[cfg(not(any(feature = "zstd", feature = "gzip")))]
+compile_error!("the compression feature needs at least one of zstd and gzip");
+
+use crate::{decompress_error, ElfLibrary, OpenFlags, Result};
+use alloc::vec::Vec;
+
+/// The compression format of a dynamic library passed to [`ElfLibrary::from_compressed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Codec {
+    /// Zstandard (`.so.zst`)
+    #[cfg(feature = "zstd")]
+    Zstd,
+    /// gzip (`.so.gz`)
+    #[cfg(feature = "gzip")]
+    Gzip,
+}
+
+// 解压后的库的最大长度，避免损坏或恶意的压缩数据耗尽内存
+const MAX_DECOMPRESSED_SIZE: usize = 1 << 30;
+
+#[cfg(feature = "zstd")]
+fn decompress_zstd(bytes: &[u8]) -> Result<Vec<u8>> {
+    use ruzstd::io::Read;
+    let mut source = bytes;
+    let mut decoder = ruzstd::StreamingDecoder::new(&mut source)
+        .map_err(|err| decompress_error(alloc::format!("zstd: {}", err)))?;
+    let mut out = Vec::new();
+    let mut buf = [0u8; 4096];
+    loop {
+        let len = decoder
+            .read(&mut buf)
+            .map_err(|err| decompress_error(alloc::format!("zstd: {:?}", err)))?;
+        if len == 0 {
+            break;
+        }
+        if out.len() + len > MAX_DECOMPRESSED_SIZE {
+            return Err(decompress_error(
+                "zstd: the decompressed library is too large",
+            ));
+        }
+        out.extend_from_slice(&buf[..len]);
+    }
+    Ok(out)
+}
+
+// gzip尾部使用的CRC-32
+#[cfg(feature = "gzip")]
+const CRC_TABLE: [u32; 256] = {
+    let mut table = [0u32; 256];
+    let mut i = 0;
+    while i < 256 {
+        let mut crc = i as u32;
+        let mut k = 0;
+        while k < 8 {
+            crc = if crc & 1 != 0 {
+                0xedb8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+            k += 1;
+        }
+        table[i] = crc;
+        i += 1;
+    }
+    table
+};
+
+#[cfg(feature = "gzip")]
+fn crc32(data: &[u8]) -> u32 {
+    !data.iter().fold(!0u32, |crc, byte| {
+        CRC_TABLE[((crc ^ *byte as u32) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// Get the length of the header of a gzip member.
+#[cfg(feature = "gzip")]
+fn gzip_header_len(bytes: &[u8]) -> Result<usize> {
+    const FHCRC: u8 = 2;
+    const FEXTRA: u8 = 4;
+    const FNAME: u8 = 8;
+    const FCOMMENT: u8 = 16;
+    let invalid = || decompress_error("gzip: invalid header");
+    if bytes.len() < 10 || bytes[0] != 0x1f || bytes[1] != 0x8b || bytes[2] != 8 {
+        return Err(invalid());
+    }
+    let flags = bytes[3];
+    let mut pos = 10;
+    if flags & FEXTRA != 0 {
+        let xlen = bytes.get(pos..pos + 2).ok_or_else(invalid)?;
+        pos += 2 + u16::from_le_bytes([xlen[0], xlen[1]]) as usize;
+    }
+    for flag in [FNAME, FCOMMENT] {
+        if flags & flag != 0 {
+            let end = bytes
+                .get(pos..)
+                .and_then(|rest| rest.iter().position(|b| *b == 0))
+                .ok_or_else(invalid)?;
+            pos += end + 1;
+        }
+    }
+    if flags & FHCRC != 0 {
+        pos += 2;
+    }
+    if pos > bytes.len() {
+        return Err(invalid());
+    }
+    Ok(pos)
+}
+
+/// Inflate the raw deflate data at the start of `input` to the end of `out`, and return the length of the data consumed.
+/// It works like `miniz_oxide::inflate::decompress_to_vec_with_limit`, which does not return the consumed length
+/// needed to find the trailer of the member.
+#[cfg(feature = "gzip")]
+fn inflate(input: &[u8], out: &mut Vec<u8>) -> Result<usize> {
+    use miniz_oxide::inflate::{
+        core::{
+            decompress, inflate_flags::TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF, DecompressorOxide,
+        },
+        TINFLStatus,
+    };
+    let start = out.len();
+    let mut decomp = alloc::boxed::Box::<DecompressorOxide>::default();
+    let mut consumed = 0;
+    let mut out_pos = 0;
+    out.resize(
+        (start + input.len().saturating_mul(2)).min(MAX_DECOMPRESSED_SIZE),
+        0,
+    );
+    loop {
+        let (status, in_len, out_len) = decompress(
+            &mut decomp,
+            &input[consumed..],
+            &mut out[start..],
+            out_pos,
+            TINFL_FLAG_USING_NON_WRAPPING_OUTPUT_BUF,
+        );
+        consumed += in_len;
+        out_pos += out_len;
+        match status {
+            TINFLStatus::Done => {
+                out.truncate(start + out_pos);
+                return Ok(consumed);
+            }
+            TINFLStatus::HasMoreOutput if out.len() < MAX_DECOMPRESSED_SIZE => {
+                out.resize(out.len().saturating_mul(2).min(MAX_DECOMPRESSED_SIZE), 0);
+            }
+            TINFLStatus::HasMoreOutput => {
+                return Err(decompress_error(
+                    "gzip: the decompressed library is too large",
+                ));
+            }
+            status => return Err(decompress_error(alloc::format!("gzip: {:?}", status))),
+        }
+    }
+}
+
+#[cfg(feature = "gzip")]
+fn decompress_gzip(bytes: &[u8]) -> Result<Vec<u8>> {
+    let mut out = Vec::new();
+    let mut rest = bytes;
+    // gzip文件可以由多个成员连接而成，它们解压后的数据也依次连接
+    loop {
+        let start = out.len();
+        let header = gzip_header_len(rest)?;
+        let end = header + inflate(&rest[header..], &mut out)?;
+        // 尾部是解压后数据的CRC-32和长度(模2^32)
+        let trailer = rest
+            .get(end..end + 8)
+            .ok_or_else(|| decompress_error("gzip: truncated trailer"))?;
+        let crc = u32::from_le_bytes([trailer[0], trailer[1], trailer[2], trailer[3]]);
+        let len = u32::from_le_bytes([trailer[4], trailer[5], trailer[6], trailer[7]]);
+        let member = &out[start..];
+        if crc32(member) != crc || member.len() as u32 != len {
+            return Err(decompress_error(
+                "gzip: the checksum or the length in the trailer does not match",
+            ));
+        }
+        rest = &rest[end + 8..];
+        if rest.is_empty() {
+            return Ok(out);
+        }
+    }
+}
+
+impl ElfLibrary {
+    /// Load a compressed elf dynamic library from bytes.
+    /// The bytes are decompressed into an owned buffer, which is then loaded the same way as [`ElfLibrary::from_binary`].
+    /// The `flags` argument can control how dynamic libraries are loaded.
+    ///
+    /// # Note
+    /// The decompressed library is always copied into anonymous memory, so unlike [`ElfLibrary::from_file`],
+    /// its pages can not be shared with other processes or paged in lazily from the file.
+    /// Libraries larger than 1 GiB after decompression are refused.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{Codec, ElfLibrary, OpenFlags};
+    /// let bytes = std::fs::read("/path/to/awesome.module.zst").unwrap();
+    /// let lib = ElfLibrary::from_compressed(&bytes, "/path/to/awesome.module", OpenFlags::RTLD_LOCAL, Codec::Zstd).unwrap();
+    /// ```
+    pub fn from_compressed(
+        bytes: impl AsRef<[u8]>,
+        path: impl AsRef<str>,
+        flags: OpenFlags,
+        codec: Codec,
+    ) -> Result<Self> {
+        let bytes = bytes.as_ref();
+        let decompressed = match codec {
+            #[cfg(feature = "zstd")]
+            Codec::Zstd => decompress_zstd(bytes)?,
+            #[cfg(feature = "gzip")]
+            Codec::Gzip => decompress_gzip(bytes)?,
+        };
+        log::debug!(
+            "Decompressed [{}] from {} bytes to {} bytes",
+            path.as_ref(),
+            bytes.len(),
+            decompressed.len()
+        );
+        Self::from_binary(&decompressed, path, flags)
+    }
+}
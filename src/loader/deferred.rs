@@ -0,0 +1,232 @@
+use crate::OpenFlags;
+use alloc::vec::Vec;
+use core::{
+    ffi::{c_char, c_int},
+    sync::atomic::{AtomicU8, AtomicUsize, Ordering},
+};
+use elf_loader::{
+    abi::{DT_INIT, DT_INIT_ARRAY, DT_INIT_ARRAYSZ, DT_NULL},
+    arch::Dyn,
+    CoreComponent,
+};
+
+// elf_loader会忽略这个tag，用它替换DT_INIT和DT_INIT_ARRAY，这样relocate时就不会调用init函数
+pub(crate) const DT_DLOPEN_RS_HIDDEN: i64 = 0x6ffff000;
+
+/// The entries of the dynamic section whose tags have been replaced with `DT_DLOPEN_RS_HIDDEN`,
+/// as `(index, original tag)`.
+pub(crate) type HiddenTags = Vec<(usize, i64)>;
+
+/// Replace the tag of `entry` with `DT_DLOPEN_RS_HIDDEN` and remember the original tag in `hidden`.
+#[inline]
+pub(crate) unsafe fn hide_tag(dynamic: *mut Dyn, entry: *mut Dyn, hidden: &mut HiddenTags) {
+    hidden.push((entry.offset_from(dynamic) as usize, (*entry).d_tag));
+    (*entry).d_tag = DT_DLOPEN_RS_HIDDEN;
+}
+
+type InitFn = extern "C" fn(c_int, *const *const c_char, *const *const c_char);
+
+// glibc会将argc、argv和envp传给.init_array中的函数，Rust的std在glibc上依赖它们实现std::env::args，
+// 而elf_loader调用init函数时不传参数，所以需要保存程序的参数，自己调用init函数
+#[cfg(all(feature = "std", target_env = "gnu"))]
+mod args {
+    use super::InitFn;
+    use core::{
+        ffi::{c_char, c_int},
+        ptr::null_mut,
+        sync::atomic::{AtomicIsize, AtomicPtr, Ordering},
+    };
+
+    static ARGC: AtomicIsize = AtomicIsize::new(0);
+    static ARGV: AtomicPtr<*const c_char> = AtomicPtr::new(null_mut());
+    static ENVP: AtomicPtr<*const c_char> = AtomicPtr::new(null_mut());
+
+    #[used]
+    #[link_section = ".init_array.00099"]
+    static SAVE_ARGS: InitFn = save_args;
+
+    extern "C" fn save_args(argc: c_int, argv: *const *const c_char, envp: *const *const c_char) {
+        ARGC.store(argc as isize, Ordering::Relaxed);
+        ARGV.store(argv as _, Ordering::Relaxed);
+        ENVP.store(envp as _, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(super) fn get() -> (c_int, *const *const c_char, *const *const c_char) {
+        (
+            ARGC.load(Ordering::Relaxed) as c_int,
+            ARGV.load(Ordering::Relaxed) as _,
+            ENVP.load(Ordering::Relaxed) as _,
+        )
+    }
+}
+
+#[cfg(not(all(feature = "std", target_env = "gnu")))]
+mod args {
+    use core::{
+        ffi::{c_char, c_int},
+        ptr::null,
+    };
+
+    #[inline]
+    pub(super) fn get() -> (c_int, *const *const c_char, *const *const c_char) {
+        (0, null(), null())
+    }
+}
+
+// init函数的状态
+const PENDING: u8 = 0;
+const RUNNING: u8 = 1;
+const DONE: u8 = 2;
+
+// 用于识别调用init函数的线程，no_std下不区分线程
+#[cfg(feature = "std")]
+fn thread_id() -> usize {
+    std::thread_local! {
+        static ID: u8 = const { 0 };
+    }
+    ID.with(|id| id as *const u8 as usize)
+}
+
+#[cfg(not(feature = "std"))]
+fn thread_id() -> usize {
+    0
+}
+
+/// The init functions of a library. They are hidden from elf_loader and called by dlopen-rs after relocation,
+/// or later by `Dylib::run_init` if `OpenFlags::CUSTOM_DEFER_INIT` is used.
+pub(crate) struct DeferredInit {
+    init_off: Option<usize>,
+    init_array: Option<(usize, usize)>,
+    state: AtomicU8,
+    // 正在调用init函数的线程
+    owner: AtomicUsize,
+}
+
+impl DeferredInit {
+    /// Hide DT_INIT and DT_INIT_ARRAY in the dynamic section, so that elf_loader will not call them.
+    pub(crate) unsafe fn hide(dynamic: *mut Dyn, hidden: &mut HiddenTags) -> DeferredInit {
+        let mut init_off = None;
+        let mut init_array_off = None;
+        let mut init_array_size = 0;
+        let mut cur = dynamic;
+        while (*cur).d_tag != DT_NULL {
+            match (*cur).d_tag {
+                DT_INIT => {
+                    init_off = Some((*cur).d_un as usize);
+                    hide_tag(dynamic, cur, hidden);
+                }
+                DT_INIT_ARRAY => {
+                    init_array_off = Some((*cur).d_un as usize);
+                    hide_tag(dynamic, cur, hidden);
+                }
+                DT_INIT_ARRAYSZ => init_array_size = (*cur).d_un as usize,
+                _ => {}
+            }
+            cur = cur.add(1);
+        }
+        DeferredInit {
+            init_off,
+            init_array: init_array_off.map(|off| (off, init_array_size / size_of::<usize>())),
+            state: AtomicU8::new(PENDING),
+            owner: AtomicUsize::new(0),
+        }
+    }
+
+    /// Whether the init functions have not been called yet.
+    #[inline]
+    pub(crate) fn is_pending(&self) -> bool {
+        self.state.load(Ordering::Acquire) == PENDING
+    }
+
+    /// Mark the init functions as being called by the current thread, it returns false if they have already been claimed.
+    pub(crate) fn claim(&self) -> bool {
+        if self
+            .state
+            .compare_exchange(PENDING, RUNNING, Ordering::AcqRel, Ordering::Acquire)
+            .is_err()
+        {
+            return false;
+        }
+        self.owner.store(thread_id(), Ordering::Release);
+        true
+    }
+
+    /// Call the claimed init functions with `argc`, `argv` and `envp` like glibc does.
+    pub(crate) unsafe fn run(&self, base: usize) {
+        let (argc, argv, envp) = args::get();
+        if let Some(off) = self.init_off {
+            let init: InitFn = core::mem::transmute(base + off);
+            init(argc, argv, envp);
+        }
+        if let Some((off, len)) = self.init_array {
+            let array: &[InitFn] = core::slice::from_raw_parts((base + off) as _, len);
+            for init in array {
+                init(argc, argv, envp);
+            }
+        }
+        self.state.store(DONE, Ordering::Release);
+    }
+
+    /// Wait until another thread has finished calling the init functions.
+    pub(crate) fn wait(&self) {
+        while self.state.load(Ordering::Acquire) == RUNNING {
+            // init函数中加载了依赖这个库的库，等待会导致死锁
+            if self.owner.load(Ordering::Acquire) == thread_id() {
+                return;
+            }
+            core::hint::spin_loop();
+        }
+    }
+
+    /// Call the init functions, it does nothing if they have already been called.
+    pub(crate) unsafe fn call_init(&self, base: usize) -> bool {
+        if !self.claim() {
+            self.wait();
+            return false;
+        }
+        self.run(base);
+        true
+    }
+}
+
+#[inline]
+pub(crate) fn get(core: &CoreComponent) -> Option<&DeferredInit> {
+    core.user_data()
+        .get(super::DEFERRED_INIT_ID)
+        .and_then(|data| data.downcast_ref::<DeferredInit>())
+}
+
+/// Call the init functions of a relocated library, unless they are deferred by `OpenFlags::CUSTOM_DEFER_INIT`.
+#[inline]
+pub(crate) fn init(core: &CoreComponent, flags: OpenFlags) {
+    if flags.contains(OpenFlags::CUSTOM_DEFER_INIT) {
+        return;
+    }
+    if let Some(deferred) = get(core) {
+        unsafe { deferred.call_init(core.base()) };
+    }
+}
+
+/// Claim the init functions of a library before it is visible to other threads, unless they are deferred by
+/// `OpenFlags::CUSTOM_DEFER_INIT`. The claimed init functions must be called with [`run`].
+#[inline]
+pub(crate) fn claim(core: &CoreComponent, flags: OpenFlags) -> bool {
+    !flags.contains(OpenFlags::CUSTOM_DEFER_INIT) && get(core).is_some_and(|init| init.claim())
+}
+
+/// Call the init functions claimed by [`claim`].
+#[inline]
+pub(crate) fn run(core: &CoreComponent) {
+    if let Some(deferred) = get(core) {
+        unsafe { deferred.run(core.base()) };
+    }
+}
+
+/// Wait until the init functions of a library called by another thread return.
+#[inline]
+pub(crate) fn wait(core: &CoreComponent) {
+    if let Some(deferred) = get(core) {
+        deferred.wait();
+    }
+}
@@ -0,0 +1,155 @@
+use crate::Result;
+use alloc::{format, vec::Vec};
+use elf_loader::{
+    abi::PT_DYNAMIC,
+    arch::{Dyn, Phdr},
+    object::ElfObject,
+    parse_phdr_error, Error,
+};
+
+const DT_FLAGS: i64 = 30;
+const DT_FLAGS_1: i64 = 0x6ffffffb;
+const DT_TEXTREL: i64 = 22;
+const DT_BIND_NOW: i64 = 24;
+const DF_TEXTREL: u64 = 0x4;
+const DF_BIND_NOW: u64 = 0x8;
+const DF_1_NOW: u64 = 0x1;
+
+const ELFMAG: [u8; 4] = *b"\x7fELF";
+const EI_CLASS: usize = 4;
+const EI_DATA: usize = 5;
+// 支持的架构都是64位的
+const ELFCLASS64: u8 = 2;
+#[cfg(target_endian = "little")]
+const ELFDATA_NATIVE: u8 = 1;
+#[cfg(target_endian = "big")]
+const ELFDATA_NATIVE: u8 = 2;
+// ELF64头部中e_phoff和e_phnum的偏移
+const E_PHOFF: usize = 0x20;
+const E_PHNUM: usize = 0x38;
+const EHDR_SIZE: usize = 0x40;
+
+/// Get the program headers of a library mapped by the system dynamic linker, whose elf header is mapped at `base`.
+/// It returns an empty slice if there is no elf header at `base`.
+pub(crate) unsafe fn mapped_phdrs(base: usize) -> &'static [Phdr] {
+    let ehdr = core::slice::from_raw_parts(base as *const u8, EHDR_SIZE);
+    if ehdr[..4] != ELFMAG || ehdr[EI_CLASS] != ELFCLASS64 {
+        return &[];
+    }
+    let phoff = u64::from_ne_bytes(ehdr[E_PHOFF..E_PHOFF + 8].try_into().unwrap()) as usize;
+    let phnum = u16::from_ne_bytes(ehdr[E_PHNUM..E_PHNUM + 2].try_into().unwrap()) as usize;
+    core::slice::from_raw_parts((base + phoff) as *const Phdr, phnum)
+}
+
+/// Read a table of `len` entries at `offset` from an elf object whose size is `size`.
+/// The offset and the length come from the file, so they are checked against the size before allocating the table.
+pub(crate) fn read_table<T>(
+    object: &mut impl ElfObject,
+    size: usize,
+    offset: usize,
+    len: usize,
+) -> Result<Vec<T>> {
+    let in_file = len
+        .checked_mul(size_of::<T>())
+        .and_then(|bytes| bytes.checked_add(offset))
+        .is_some_and(|end| end <= size);
+    if !in_file {
+        return Err(parse_phdr_error(format!(
+            "{}: a table at offset 0x{:x} with {} entries is out of the file",
+            object.file_name().to_string_lossy(),
+            offset,
+            len
+        ))
+        .into());
+    }
+    let mut table = Vec::<T>::with_capacity(len);
+    unsafe {
+        table.as_mut_ptr().write_bytes(0, len);
+        object.read(
+            core::slice::from_raw_parts_mut(table.as_mut_ptr().cast::<u8>(), len * size_of::<T>()),
+            offset,
+        )?;
+        table.set_len(len);
+    }
+    Ok(table)
+}
+
+/// The flags in `DT_FLAGS` and `DT_FLAGS_1` that affect how a library is relocated.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct DynFlags {
+    /// `DF_BIND_NOW`, `DF_1_NOW` or `DT_BIND_NOW`: all symbols must be bound before `dlopen` returns.
+    pub(crate) bind_now: bool,
+    /// `DF_TEXTREL` or `DT_TEXTREL`: there are relocations against read-only segments.
+    pub(crate) textrel: bool,
+    /// The object is an elf file without a `PT_DYNAMIC` segment, so it is not a dynamic library.
+    pub(crate) no_dynamic: bool,
+    /// `e_phnum` in the elf header.
+    pub(crate) phnum: usize,
+}
+
+impl DynFlags {
+    /// Read the flags from the dynamic section of an unmapped elf object whose size is `size`.
+    pub(crate) fn read(object: &mut impl ElfObject, size: usize) -> Result<DynFlags> {
+        let name = object.file_name().to_string_lossy().into_owned();
+        if size < EHDR_SIZE {
+            return Err(Error::ParseEhdrError {
+                msg: format!("{}: file is too short", name),
+            }
+            .into());
+        }
+        let mut ehdr = [0u8; EHDR_SIZE];
+        object.read(&mut ehdr, 0)?;
+        let mut flags = DynFlags::default();
+        // 不是elf文件时交给elf_loader报错
+        if ehdr[..4] != ELFMAG {
+            return Ok(flags);
+        }
+        // 之后按ELF64的布局读取头部
+        if ehdr[EI_CLASS] != ELFCLASS64 {
+            return Err(Error::ParseEhdrError {
+                msg: format!("{}: not a 64-bit elf file", name),
+            }
+            .into());
+        }
+        // 字节序不同时，之后读取的所有字段都是错误的
+        if ehdr[EI_DATA] != ELFDATA_NATIVE {
+            return Err(Error::ParseEhdrError {
+                msg: format!("{}: endianness mismatch", name),
+            }
+            .into());
+        }
+        let phoff = u64::from_ne_bytes(ehdr[E_PHOFF..E_PHOFF + 8].try_into().unwrap()) as usize;
+        let phnum = u16::from_ne_bytes(ehdr[E_PHNUM..E_PHNUM + 2].try_into().unwrap()) as usize;
+        flags.phnum = phnum;
+        if phnum == 0 {
+            flags.no_dynamic = true;
+            return Ok(flags);
+        }
+        let phdrs: Vec<Phdr> = read_table(object, size, phoff, phnum)?;
+        let Some(dynamic) = phdrs.iter().find(|phdr| phdr.p_type == PT_DYNAMIC) else {
+            flags.no_dynamic = true;
+            return Ok(flags);
+        };
+        let dynamic: Vec<Dyn> = read_table(
+            object,
+            size,
+            dynamic.p_offset as usize,
+            dynamic.p_filesz as usize / size_of::<Dyn>(),
+        )?;
+        for entry in &dynamic {
+            match entry.d_tag {
+                DT_FLAGS => {
+                    let val = entry.d_un;
+                    flags.bind_now |= val & DF_BIND_NOW != 0;
+                    flags.textrel |= val & DF_TEXTREL != 0;
+                }
+                DT_FLAGS_1 => flags.bind_now |= entry.d_un & DF_1_NOW != 0,
+                DT_BIND_NOW => flags.bind_now = true,
+                DT_TEXTREL => flags.textrel = true,
+                0 => break,
+                _ => {}
+            }
+        }
+        Ok(flags)
+    }
+}
@@ -22,3 +22,34 @@ cfg_if::cfg_if! {
 }
 
 pub(crate) use imp::EhFrame;
+
+const DW_EH_PE_OMIT: u8 = 0xff;
+const DW_EH_PE_PCREL: u8 = 0x10;
+const DW_EH_PE_DATAREL: u8 = 0x30;
+
+/// Decode the `eh_frame_ptr` field of the `.eh_frame_hdr` at `eh_frame_hdr`, which is the address of the `.eh_frame` section.
+pub(crate) unsafe fn eh_frame_ptr(eh_frame_hdr: usize) -> Option<usize> {
+    let hdr = eh_frame_hdr as *const u8;
+    // version必须为1
+    if *hdr != 1 {
+        return None;
+    }
+    let enc = *hdr.add(1);
+    if enc == DW_EH_PE_OMIT {
+        return None;
+    }
+    let field = hdr.add(4);
+    let value = match enc & 0x0f {
+        0x00 => field.cast::<usize>().read_unaligned(),
+        0x03 => field.cast::<u32>().read_unaligned() as usize,
+        0x0b => field.cast::<i32>().read_unaligned() as isize as usize,
+        0x04 | 0x0c => field.cast::<u64>().read_unaligned() as usize,
+        _ => return None,
+    };
+    match enc & 0x70 {
+        0 => Some(value),
+        DW_EH_PE_PCREL => Some((field as usize).wrapping_add(value)),
+        DW_EH_PE_DATAREL => Some(eh_frame_hdr.wrapping_add(value)),
+        _ => None,
+    }
+}
@@ -0,0 +1,75 @@
+use crate::register::registry_name;
+use alloc::vec::Vec;
+use core::ffi::CStr;
+use elf_loader::{
+    abi::{DT_NULL, DT_STRTAB, PT_DYNAMIC},
+    arch::Dyn,
+    CoreComponent,
+};
+
+const DT_AUXILIARY: i64 = 0x7ffffffd;
+const DT_FILTER: i64 = 0x7fffffff;
+
+/// Get the names of the filtees of a mapped library, which are recorded in `DT_FILTER` and `DT_AUXILIARY`.
+/// The flag is `true` for `DT_AUXILIARY`, whose filtees are optional.
+pub(crate) unsafe fn filtees<'a>(lib: &CoreComponent) -> Vec<(&'a str, bool)> {
+    let Some(dynamic) = super::phdrs(lib)
+        .iter()
+        .find(|phdr| phdr.p_type == PT_DYNAMIC)
+    else {
+        return Vec::new();
+    };
+    let base = lib.base();
+    let mut strtab = 0;
+    let mut names = Vec::new();
+    let mut cur = (base + dynamic.p_vaddr as usize) as *const Dyn;
+    while (*cur).d_tag != DT_NULL {
+        let entry = &*cur;
+        match entry.d_tag {
+            DT_STRTAB => strtab = entry.d_un as usize,
+            DT_FILTER => names.push((entry.d_un as usize, false)),
+            DT_AUXILIARY => names.push((entry.d_un as usize, true)),
+            _ => {}
+        }
+        cur = cur.add(1);
+    }
+    if names.is_empty() {
+        return Vec::new();
+    }
+    // 系统动态链接器加载的库，其动态段中的地址已经被修正为绝对地址
+    let strtab = if strtab >= base {
+        strtab
+    } else {
+        base + strtab
+    };
+    names
+        .into_iter()
+        .filter_map(|(off, aux)| {
+            let name = CStr::from_ptr((strtab + off) as _).to_str().ok()?;
+            Some((name, aux))
+        })
+        .collect()
+}
+
+/// Build the scope used to relocate `libs`, where the filtees are placed before their filters,
+/// so that the symbols of a filter are looked up in its filtees first.
+pub(crate) fn scope(libs: &[CoreComponent]) -> Vec<CoreComponent> {
+    let mut scope: Vec<CoreComponent> = Vec::with_capacity(libs.len());
+    let mut push = |lib: &CoreComponent| {
+        if !scope.iter().any(|cur| cur.base() == lib.base()) {
+            scope.push(lib.clone());
+        }
+    };
+    for lib in libs {
+        for (name, _) in unsafe { filtees(lib) } {
+            if let Some(filtee) = libs
+                .iter()
+                .find(|dep| registry_name(dep) == name || dep.shortname() == name)
+            {
+                push(filtee);
+            }
+        }
+        push(lib);
+    }
+    scope
+}
@@ -0,0 +1,73 @@
+use super::reltype::{REL_COPY, REL_TPOFF};
+use alloc::collections::BTreeMap;
+use elf_loader::{
+    abi::{DT_JMPREL, DT_NULL, DT_PLTRELSZ, DT_RELA, DT_RELASZ, PT_DYNAMIC},
+    arch::{
+        Dyn, ElfRela, Phdr, REL_DTPMOD, REL_DTPOFF, REL_GOT, REL_IRELATIVE, REL_JUMP_SLOT,
+        REL_RELATIVE, REL_SYMBOLIC,
+    },
+};
+
+#[inline]
+fn bucket(r_type: u32) -> &'static str {
+    match r_type as _ {
+        REL_RELATIVE => "relative",
+        REL_GOT => "glob_dat",
+        REL_JUMP_SLOT => "jump_slot",
+        REL_SYMBOLIC => "symbolic",
+        REL_DTPMOD | REL_DTPOFF | REL_TPOFF => "tls",
+        REL_IRELATIVE => "irelative",
+        REL_COPY => "copy",
+        _ => "other",
+    }
+}
+
+/// Get the relocation entries in `.rela.dyn` and `.rela.plt`.
+pub(crate) unsafe fn relocations<'a>(
+    base: usize,
+    phdrs: &[Phdr],
+) -> impl Iterator<Item = &'a ElfRela> {
+    let mut rela = None;
+    let mut rela_size = 0;
+    let mut jmprel = None;
+    let mut jmprel_size = 0;
+    // 系统动态链接器加载的库，其动态段中的地址已经被修正为绝对地址
+    let addr = move |ptr: usize| if ptr >= base { ptr } else { base + ptr };
+    if let Some(dynamic) = phdrs.iter().find(|phdr| phdr.p_type == PT_DYNAMIC) {
+        let mut cur = (base + dynamic.p_vaddr as usize) as *const Dyn;
+        while (*cur).d_tag != DT_NULL {
+            let entry = &*cur;
+            match entry.d_tag {
+                DT_RELA => rela = Some(entry.d_un as usize),
+                DT_RELASZ => rela_size = entry.d_un as usize,
+                DT_JMPREL => jmprel = Some(entry.d_un as usize),
+                DT_PLTRELSZ => jmprel_size = entry.d_un as usize,
+                _ => {}
+            }
+            cur = cur.add(1);
+        }
+    }
+    [(rela, rela_size), (jmprel, jmprel_size)]
+        .into_iter()
+        .filter_map(move |(off, size)| {
+            off.map(|off| {
+                core::slice::from_raw_parts(
+                    addr(off) as *const ElfRela,
+                    size / size_of::<ElfRela>(),
+                )
+            })
+        })
+        .flatten()
+}
+
+/// Count the relocation entries in `.rela.dyn` and `.rela.plt` by relocation type.
+pub(crate) unsafe fn relocation_histogram(
+    base: usize,
+    phdrs: &[Phdr],
+) -> BTreeMap<&'static str, usize> {
+    let mut histogram = BTreeMap::new();
+    for rela in relocations(base, phdrs) {
+        *histogram.entry(bucket(rela.r_type() as _)).or_insert(0) += 1;
+    }
+    histogram
+}
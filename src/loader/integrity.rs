@@ -0,0 +1,57 @@
+use super::{from_impl, origin_of};
+use crate::{integrity_error, ElfLibrary, OpenFlags, Result};
+use alloc::{format, string::String};
+use core::fmt::Write;
+use elf_loader::object::ElfBinary;
+use sha2::{Digest, Sha256};
+
+fn to_hex(digest: &[u8]) -> String {
+    let mut hex = String::with_capacity(digest.len() * 2);
+    for byte in digest {
+        let _ = write!(hex, "{:02x}", byte);
+    }
+    hex
+}
+
+impl ElfLibrary {
+    /// Load a elf dynamic library from path after checking that the sha256 digest of the file is `expected_sha256`.
+    /// The `flags` argument can control how dynamic libraries are loaded.
+    ///
+    /// The file is read into memory once, and the library is loaded from the bytes that were hashed,
+    /// so replacing the file after the check has no effect on the loaded library.
+    ///
+    /// # Note
+    /// Like [`ElfLibrary::from_binary`], the library is copied into anonymous memory instead of being mapped from the file.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use ::dlopen_rs::{ElfLibrary, OpenFlags};
+    /// const DIGEST: [u8; 32] = [0; 32];
+    /// let lib = ElfLibrary::from_file_verified("/path/to/awesome.module", &DIGEST, OpenFlags::RTLD_LOCAL)
+    ///     .unwrap();
+    /// ```
+    pub fn from_file_verified(
+        path: impl AsRef<std::ffi::OsStr>,
+        expected_sha256: &[u8; 32],
+        flags: OpenFlags,
+    ) -> Result<Self> {
+        let path = path.as_ref().to_str().unwrap();
+        let bytes = std::fs::read(path)?;
+        let digest = Sha256::digest(&bytes);
+        if digest.as_slice() != expected_sha256 {
+            return Err(integrity_error(format!(
+                "[{}] sha256 mismatch: expected {}, found {}",
+                path,
+                to_hex(expected_sha256),
+                to_hex(&digest)
+            )));
+        }
+        log::debug!("Verified the sha256 digest of [{}]", path);
+        from_impl(
+            ElfBinary::new(path, &bytes),
+            bytes.len(),
+            flags,
+            Some(origin_of(path)),
+        )
+    }
+}
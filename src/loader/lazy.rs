@@ -0,0 +1,57 @@
+use alloc::boxed::Box;
+use spin::RwLock;
+
+/// What the lazy binding resolver does with a symbol that can not be found, see [`set_lazy_failure_handler`].
+#[derive(Clone, Copy, Debug)]
+pub enum LazyAction {
+    /// Fail the binding, which aborts the process like before.
+    Abort,
+    /// Bind the `.got.plt` slot to this address instead, e.g. a stub that reports the missing symbol.
+    Return(*const ()),
+}
+
+type LazyFailureHandler = Box<dyn Fn(&str) -> LazyAction + Send + Sync>;
+
+static HANDLER: RwLock<Option<LazyFailureHandler>> = RwLock::new(None);
+
+/// Set a function that is called when the lazy binding resolver can not find a symbol on its first call,
+/// for the libraries loaded with `OpenFlags::RTLD_LAZY`. It receives the name of the symbol, and replaces the previously set function.
+///
+/// # Note
+/// The function is called on the thread that calls the missing function, inside the resolver trampoline,
+/// so it must not load or unload libraries.
+///
+/// # Examples
+/// ```no_run
+/// use dlopen_rs::LazyAction;
+///
+/// extern "C" fn missing() {
+///     panic!("called a missing lazy symbol");
+/// }
+///
+/// dlopen_rs::set_lazy_failure_handler(|name| {
+///     eprintln!("missing lazy symbol [{}]", name);
+///     LazyAction::Return(missing as *const ())
+/// });
+/// ```
+pub fn set_lazy_failure_handler(handler: impl Fn(&str) -> LazyAction + Send + Sync + 'static) {
+    *HANDLER.write() = Some(Box::new(handler));
+}
+
+#[inline]
+pub(crate) fn on_failure(name: &str) -> Option<*const ()> {
+    let action = HANDLER
+        .read()
+        .as_ref()
+        .map_or(LazyAction::Abort, |handler| handler(name));
+    match action {
+        LazyAction::Abort => {
+            log::error!("Lazy binding failed! Can not find symbol [{}]", name);
+            None
+        }
+        LazyAction::Return(ptr) => {
+            log::warn!("Bind the missing lazy symbol [{}] to [{:p}]", name, ptr);
+            Some(ptr)
+        }
+    }
+}
@@ -1,30 +1,105 @@
+#[cfg(feature = "std")]
+mod builder;
 pub(crate) mod builtin;
+#[cfg(feature = "compression")]
+pub(crate) mod compressed;
+pub(crate) mod deferred;
+pub(crate) mod dynflags;
 pub(crate) mod ehframe;
+pub(crate) mod filter;
+mod histogram;
+#[cfg(feature = "verification")]
+mod integrity;
+pub(crate) mod lazy;
+pub(crate) mod note;
+pub(crate) mod property;
+mod relr;
+mod reltype;
+pub(crate) mod resolver;
+#[cfg(feature = "sections")]
+mod sections;
+#[cfg(not(feature = "std"))]
+pub(crate) mod staged;
+pub(crate) mod symbolic;
+pub(crate) mod symkind;
+#[cfg(feature = "version")]
+mod symver;
+#[cfg(feature = "std")]
+pub(crate) mod textrel;
 pub(crate) mod tls;
+#[cfg(feature = "mmap")]
+mod trim;
+pub(crate) mod unknown;
 
 #[cfg(feature = "debug")]
 use super::debug::DebugInfo;
 use crate::{
+    audit,
+    event::{emit, LoadEvent},
     find_lib_error, find_symbol_error,
-    register::{register, MANAGER},
-    OpenFlags, Result,
+    register::{register, registry_name, Lmid, Manager, MANAGER},
+    Error, OpenFlags, Result,
 };
-use alloc::{boxed::Box, format, sync::Arc, vec::Vec};
-use core::{ffi::CStr, fmt::Debug, marker::PhantomData};
+use alloc::{boxed::Box, collections::BTreeMap, format, string::String, sync::Arc, vec::Vec};
+#[cfg(feature = "std")]
+pub use builder::LoadBuilder;
+use core::{
+    ffi::CStr,
+    fmt::Debug,
+    marker::PhantomData,
+    ops::{Deref, Range},
+    sync::atomic::{AtomicBool, Ordering},
+};
+use deferred::{DeferredInit, HiddenTags};
+use dynflags::DynFlags;
 use ehframe::EhFrame;
 use elf_loader::{
-    abi::PT_GNU_EH_FRAME,
-    arch::{ElfRela, Phdr},
+    abi::{
+        DT_NULL, DT_SONAME, DT_STRTAB, PF_X, PT_DYNAMIC, PT_GNU_EH_FRAME, PT_GNU_STACK, PT_INTERP,
+        PT_LOAD, PT_TLS, SHN_UNDEF, STB_WEAK,
+    },
+    arch::{Dyn, ElfRela, Phdr, REL_GOT, REL_JUMP_SLOT, REL_SYMBOLIC},
     object::{ElfBinary, ElfObject},
     segment::ElfSegments,
     CoreComponent, CoreComponentRef, ElfDylib, Loader, Symbol, UserData,
 };
+use note::OsAbi;
+use property::CpuFeatures;
+#[cfg(feature = "sections")]
+pub use sections::Section;
+use spin::RwLock;
+use symkind::{DynTables, StrongScope, SymKind};
 
 pub(crate) const EH_FRAME_ID: u8 = 0;
 #[cfg(feature = "debug")]
 pub(crate) const DEBUG_INFO_ID: u8 = 1;
 #[cfg(feature = "tls")]
 const TLS_ID: u8 = 2;
+pub(crate) const DEFERRED_INIT_ID: u8 = 3;
+const ORIGIN_ID: u8 = 4;
+const LAZY_ID: u8 = 5;
+pub(crate) const SONAME_ID: u8 = 6;
+#[cfg(feature = "sections")]
+const SECTIONS_ID: u8 = 7;
+const HIDDEN_TAGS_ID: u8 = 8;
+const PHNUM_ID: u8 = 9;
+const RELR_ID: u8 = 10;
+
+/// Get the program headers of `core`.
+/// 没有`PT_PHDR`时，elf_loader返回的程序头包含第一个段中程序头之后的所有数据，
+/// 所以这里按`e_phnum`截断
+#[inline]
+pub(crate) fn phdrs(core: &CoreComponent) -> &[Phdr] {
+    let phdrs = core.phdrs();
+    match core
+        .user_data()
+        .get(PHNUM_ID)
+        .and_then(|data| data.downcast_ref::<usize>())
+    {
+        Some(&phnum) => &phdrs[..phnum.min(phdrs.len())],
+        None => phdrs,
+    }
+}
 
 #[inline]
 pub(crate) fn find_symbol<'lib, T>(
@@ -33,14 +108,36 @@ pub(crate) fn find_symbol<'lib, T>(
 ) -> Result<Symbol<'lib, T>> {
     log::info!("Get the symbol [{}] in [{}]", name, libs[0].shortname());
     libs.iter()
-        .find_map(|lib| unsafe { lib.get::<T>(name) })
+        .find_map(|lib| unsafe {
+            // 有多个版本时使用默认版本
+            #[cfg(feature = "version")]
+            if let Some(version) = symver::default_version(lib, name) {
+                return lib.get_version::<T>(name, &version);
+            }
+            lib.get::<T>(name)
+        })
         .ok_or(find_symbol_error(format!("can not find symbol:{}", name)))
 }
 
+/// Find the symbol `name`, which may not be valid UTF-8, in `libs` in order and return its address.
+/// Names that are not valid UTF-8 are looked up in the `.dynsym` of each library by bytes.
+pub(crate) fn find_symbol_bytes<'lib>(
+    mut libs: impl Iterator<Item = &'lib CoreComponent>,
+    name: &[u8],
+) -> Option<*const ()> {
+    match core::str::from_utf8(name) {
+        Ok(name) => libs.find_map(|lib| unsafe { lib.get::<()>(name).map(|sym| sym.into_raw()) }),
+        Err(_) => libs.find_map(|lib| unsafe {
+            symkind::address(lib.base(), phdrs(lib), name).map(|addr| addr as *const ())
+        }),
+    }
+}
+
 /// An unrelocated dynamic library
 pub struct ElfLibrary {
     pub(crate) dylib: ElfDylib,
     pub(crate) flags: OpenFlags,
+    pub(crate) textrel: bool,
 }
 
 impl Debug for ElfLibrary {
@@ -56,9 +153,11 @@ fn parse_phdr(
     phdr: &Phdr,
     segments: &ElfSegments,
     data: &mut UserData,
+    flags: OpenFlags,
+    origin: Option<&str>,
 ) -> elf_loader::Result<()> {
     match phdr.p_type {
-        PT_GNU_EH_FRAME => {
+        PT_GNU_EH_FRAME if !flags.contains(OpenFlags::CUSTOM_SKIP_UNWIND_REGISTER) => {
             data.insert(
                 EH_FRAME_ID,
                 Box::new(EhFrame::new(
@@ -67,8 +166,31 @@ fn parse_phdr(
                 )),
             );
         }
-        #[cfg(feature = "debug")]
-        elf_loader::abi::PT_DYNAMIC => {
+        PT_DYNAMIC => {
+            // 是否延迟绑定在加载完成后才能确定
+            data.insert(LAZY_ID, Box::new(AtomicBool::new(false)));
+            if let Some(origin) = origin {
+                data.insert(ORIGIN_ID, Box::new(String::from(origin)));
+            }
+            if let Some(soname) =
+                unsafe { read_soname(segments.base(), segments.base() + phdr.p_vaddr as usize) }
+            {
+                data.insert(SONAME_ID, Box::new(soname));
+            }
+            let dynamic = (segments.base() + phdr.p_vaddr as usize) as _;
+            // 被隐藏的tag，dynamic_entries需要返回原来的tag
+            let mut hidden = HiddenTags::new();
+            // DT_RELR在所有的段都映射之后才能应用，见from_impl
+            if let Some(relr) = unsafe { relr::hide(dynamic, &mut hidden) } {
+                data.insert(RELR_ID, Box::new(relr));
+            }
+            // init函数由dlopen-rs调用，见deferred::init
+            data.insert(
+                DEFERRED_INIT_ID,
+                Box::new(unsafe { DeferredInit::hide(dynamic, &mut hidden) }),
+            );
+            data.insert(HIDDEN_TAGS_ID, Box::new(hidden));
+            #[cfg(feature = "debug")]
             data.insert(
                 DEBUG_INFO_ID,
                 Box::new(unsafe {
@@ -80,8 +202,15 @@ fn parse_phdr(
                 }),
             );
         }
+        PT_GNU_STACK if phdr.p_flags & PF_X != 0 => {
+            // 无法修改已经在运行的线程的栈，只能提醒使用者
+            log::warn!(
+                "[{:?}] requests an executable stack, which is not provided by dlopen-rs, see Dylib::wants_exec_stack",
+                cname
+            );
+        }
         #[cfg(feature = "tls")]
-        elf_loader::abi::PT_TLS => {
+        PT_TLS => {
             data.insert(TLS_ID, Box::new(tls::ElfTls::new(phdr, segments.base())));
         }
         _ => {}
@@ -89,6 +218,24 @@ fn parse_phdr(
     Ok(())
 }
 
+/// Read `DT_SONAME` from the dynamic section.
+unsafe fn read_soname(base: usize, dynamic: usize) -> Option<String> {
+    let mut strtab = None;
+    let mut soname = None;
+    let mut cur = dynamic as *const Dyn;
+    while (*cur).d_tag != DT_NULL {
+        let entry = &*cur;
+        match entry.d_tag {
+            DT_STRTAB => strtab = Some(entry.d_un as usize),
+            DT_SONAME => soname = Some(entry.d_un as usize),
+            _ => {}
+        }
+        cur = cur.add(1);
+    }
+    let name = CStr::from_ptr((base + strtab? + soname?) as _);
+    name.to_str().ok().map(String::from)
+}
+
 #[inline(always)]
 #[allow(unused)]
 pub(crate) fn deal_unknown<'scope>(
@@ -96,44 +243,123 @@ pub(crate) fn deal_unknown<'scope>(
     lib: &ElfDylib,
     mut deps: impl Iterator<Item = &'scope CoreComponent> + Clone,
 ) -> bool {
-    #[cfg(feature = "tls")]
+    // 重定位项来自库本身，不能信任，写入的位置必须在库的映射范围内，否则交给elf_loader报错
+    let in_bounds = |off: usize, len: usize| {
+        let res = off.checked_add(len).is_some_and(|end| end <= lib.map_len());
+        if !res {
+            log::error!(
+                "Relocating dylib [{}] failed! The relocation at offset [0x{:x}] is out of bounds",
+                lib.name(),
+                off
+            );
+        }
+        res
+    };
     match rela.r_type() as _ {
+        reltype::REL_COPY => {
+            let r_sym = rela.r_symbol();
+            let (dynsym, syminfo) = lib.symtab().symbol_idx(r_sym);
+            // 从定义该符号的库（不包括自身）中复制数据
+            if let Some((base, sym)) = deps.find_map(|core| {
+                if core.base() == lib.base() {
+                    return None;
+                }
+                core.symtab()
+                    .lookup_filter(&syminfo)
+                    .map(|sym| (core.base(), sym))
+            }) {
+                let len = (dynsym.st_size as usize).min(sym.st_size as usize);
+                if !in_bounds(rela.r_offset(), len) {
+                    return false;
+                }
+                unsafe {
+                    core::ptr::copy_nonoverlapping(
+                        (base + sym.st_value as usize) as *const u8,
+                        (lib.base() + rela.r_offset()) as *mut u8,
+                        len,
+                    )
+                };
+                return true;
+            }
+        }
+        #[cfg(feature = "tls")]
         elf_loader::arch::REL_DTPMOD => {
             let r_sym = rela.r_symbol();
             let r_off = rela.r_offset();
+            if !in_bounds(r_off, size_of::<usize>()) {
+                return false;
+            }
             let ptr = (lib.base() + r_off) as *mut usize;
-            let cast = |core: &CoreComponent| unsafe {
+            // 没有PT_TLS的库没有模块id
+            let cast = |core: &CoreComponent| {
                 core.user_data()
                     .get(TLS_ID)
-                    .unwrap()
-                    .downcast_ref::<tls::ElfTls>()
-                    .unwrap_unchecked()
-                    .module_id()
+                    .and_then(|data| data.downcast_ref::<tls::ElfTls>())
+                    .map(|tls| tls.module_id())
             };
-            if r_sym != 0 {
+            // 只读取user_data中的TLS模块id，不会使用未重定位的库中的符号
+            let this = unsafe { lib.core_component_ref() };
+            let id = if r_sym != 0 {
                 let (dynsym, syminfo) = lib.symtab().symbol_idx(r_sym);
                 if dynsym.st_info >> 4 == elf_loader::abi::STB_LOCAL {
-                    unsafe { ptr.write(cast(lib.core_component_ref())) };
-                    return true;
+                    cast(this)
                 } else {
-                    if let Some(id) = deps
-                        .find_map(|core| core.symtab().lookup_filter(&syminfo).map(|_| cast(core)))
-                    {
-                        unsafe { ptr.write(id) };
-                        return true;
-                    };
-                };
+                    deps.find_map(|core| {
+                        core.symtab()
+                            .lookup_filter(&syminfo)
+                            .and_then(|_| cast(core))
+                    })
+                }
             } else {
-                unsafe { ptr.write(cast(lib.core_component_ref())) };
+                cast(this)
+            };
+            if let Some(id) = id {
+                unsafe { ptr.write(id) };
                 return true;
             }
         }
+        // elf_loader只处理.rela.plt中的IRELATIVE，.rela.dyn中的在这里处理
+        elf_loader::arch::REL_IRELATIVE => {
+            let r_off = rela.r_offset();
+            // 加数是ifunc解析函数相对于基址的偏移，调用它得到函数的真实地址
+            let addend = rela.r_addend();
+            if !in_bounds(r_off, size_of::<usize>()) || !in_bounds(addend, 1) {
+                return false;
+            }
+            let resolver: extern "C" fn() -> usize =
+                unsafe { core::mem::transmute(lib.base() + addend) };
+            unsafe { ((lib.base() + r_off) as *mut usize).write(resolver()) };
+            return true;
+        }
         _ => {}
     }
-    log::error!("Relocating dylib [{}] failed!", lib.name());
-    false
+    unknown::handle(rela, lib)
 }
 
+/// Create the lookup used during the relocation of `lib`, which prefers a `STB_GLOBAL` definition in `strong` over the
+/// `STB_WEAK` ones before it. Definitions whose version does not satisfy the one required by `lib` are skipped.
+#[inline]
+pub(crate) fn find_strong<'a>(
+    strong: &'a StrongScope,
+    lib: &CoreComponent,
+) -> impl Fn(&str) -> Option<*const ()> + 'a {
+    #[cfg(feature = "version")]
+    let required = unsafe { symver::Requirements::new(lib) };
+    #[cfg(feature = "version")]
+    let accept =
+        move |name: &str, tables: &DynTables, idx| unsafe { required.accept(name, tables, idx) };
+    #[cfg(not(feature = "version"))]
+    let accept = {
+        let _ = lib;
+        |_: &str, _: &DynTables, _| true
+    };
+    move |name| strong.find(name, |tables, idx| accept(name, tables, idx))
+}
+
+/// Create the scope used by the lazy binding resolver.
+/// The trampoline installed in `.got.plt` calls this closure (after the global scope) on the first call
+/// of a function, so symbols that are never called are never resolved.
+/// Symbols found in neither scope are passed to the handler set by `set_lazy_failure_handler`.
 #[inline]
 pub(crate) fn create_lazy_scope(
     deps: &[CoreComponent],
@@ -141,13 +367,20 @@ pub(crate) fn create_lazy_scope(
 ) -> Option<Box<dyn for<'a> Fn(&'a str) -> Option<*const ()>>> {
     if is_lazy {
         let deps_weak: Vec<CoreComponentRef> = deps.iter().map(|dep| dep.downgrade()).collect();
+        let strong = StrongScope::new(deps.iter());
         Some(Box::new(move |name: &str| {
-            deps_weak.iter().find_map(|dep| unsafe {
-                dep.upgrade()
-                    .unwrap()
-                    .get::<()>(name)
-                    .map(|sym| sym.into_raw())
-            })
+            // 延迟绑定时只知道符号的名字，elf_loader同样不检查版本
+            strong
+                .find(name, |_, _| true)
+                .or_else(|| {
+                    deps_weak.iter().find_map(|dep| unsafe {
+                        dep.upgrade()
+                            .unwrap()
+                            .get::<()>(name)
+                            .map(|sym| sym.into_raw())
+                    })
+                })
+                .or_else(|| lazy::on_failure(name))
         })
             as Box<dyn Fn(&str) -> Option<*const ()> + 'static>)
     } else {
@@ -155,23 +388,136 @@ pub(crate) fn create_lazy_scope(
     }
 }
 
-fn from_impl(object: impl ElfObject, flags: OpenFlags) -> Result<ElfLibrary> {
+/// Zero the part of each `PT_LOAD` segment beyond `p_filesz`. Without mmap the segments are copied
+/// into an allocated buffer, so unlike an anonymous mapping the `.bss` is not guaranteed to be zeroed.
+#[cfg(not(feature = "mmap"))]
+unsafe fn zero_bss(core: &CoreComponent) {
+    for phdr in phdrs(core).iter().filter(|phdr| phdr.p_type == PT_LOAD) {
+        let len = phdr.p_memsz.saturating_sub(phdr.p_filesz) as usize;
+        if len != 0 {
+            let start = core.base() + (phdr.p_vaddr + phdr.p_filesz) as usize;
+            core::ptr::write_bytes(start as *mut u8, 0, len);
+        }
+    }
+}
+
+/// Get the directory of `path`, which is what `$ORIGIN` expands to.
+#[cfg(feature = "std")]
+#[inline]
+fn origin_of(path: &str) -> &str {
+    match path.rsplit_once('/') {
+        Some(("", _)) => "/",
+        Some((dir, _)) => dir,
+        None => ".",
+    }
+}
+
+// size是object的大小，用来检查从文件中读取的偏移和长度
+fn from_impl(
+    mut object: impl ElfObject,
+    size: usize,
+    flags: OpenFlags,
+    origin: Option<&str>,
+) -> Result<ElfLibrary> {
+    let dyn_flags = DynFlags::read(&mut object, size)?;
+    if dyn_flags.no_dynamic {
+        return Err(Error::NotADynamicLibrary {
+            name: object.file_name().to_string_lossy().into_owned(),
+        });
+    }
+    #[cfg(feature = "sections")]
+    let sections = sections::read(&mut object, size);
     let loader = Loader::<_>::new(object);
-    let lazy_bind = if flags.contains(OpenFlags::RTLD_LAZY) {
+    // DF_BIND_NOW和DF_1_NOW优先于RTLD_LAZY
+    let lazy_bind = if dyn_flags.bind_now {
+        Some(false)
+    } else if flags.contains(OpenFlags::RTLD_LAZY) {
         Some(true)
     } else if flags.contains(OpenFlags::RTLD_NOW) {
         Some(false)
     } else {
         None
     };
-    let dylib = loader.load_dylib(lazy_bind, parse_phdr)?;
+    let dylib = loader.load_dylib(lazy_bind, |cname, phdr, segments, data| {
+        if phdr.p_type == PT_DYNAMIC {
+            data.insert(PHNUM_ID, Box::new(dyn_flags.phnum));
+        }
+        #[cfg(feature = "sections")]
+        if phdr.p_type == PT_DYNAMIC {
+            data.insert(SECTIONS_ID, Box::new(sections.clone()));
+        }
+        parse_phdr(cname, phdr, segments, data, flags, origin)
+    })?;
     log::debug!(
         "Loading dylib [{}] at address [0x{:x}-0x{:x}]",
         dylib.name(),
         dylib.base(),
         dylib.base() + dylib.map_len()
     );
-    let lib = ElfLibrary { dylib, flags };
+    emit(LoadEvent::Loading {
+        name: dylib.name(),
+        addr: dylib.base(),
+    });
+    // 只读取user_data，不会使用未重定位的库中的符号
+    if let Some(lazy) = unsafe { dylib.core_component_ref() }
+        .user_data()
+        .get(LAZY_ID)
+        .and_then(|data| data.downcast_ref::<AtomicBool>())
+    {
+        lazy.store(dylib.is_lazy(), Ordering::Release);
+    }
+    #[cfg(not(feature = "mmap"))]
+    unsafe {
+        zero_bss(dylib.core_component_ref())
+    };
+    // 只读取user_data，不会使用未重定位的库中的符号
+    if let Some(relr) = unsafe { dylib.core_component_ref() }
+        .user_data()
+        .get(RELR_ID)
+        .and_then(|data| data.downcast_ref::<relr::Relr>())
+    {
+        unsafe { relr::apply(&dylib, relr)? };
+    }
+    // 检查哈希表和重定位项中的符号索引，避免损坏的库让elf_loader越界读取符号表
+    {
+        // 只读取动态段和重定位表，不会使用未重定位的库中的符号
+        let core = unsafe { dylib.core_component_ref() };
+        let count = unsafe { symkind::symbol_count(core.base(), phdrs(core)) }
+            .map_err(|err| find_symbol_error(format!("[{}]: {}", dylib.name(), err)))?;
+        if let Some(count) = count {
+            if let Some(rela) = unsafe { histogram::relocations(core.base(), phdrs(core)) }
+                .find(|rela| rela.r_symbol() >= count)
+            {
+                return Err(find_symbol_error(format!(
+                    "[{}] has a relocation at [0x{:x}] with the symbol index {}, but there are only {} symbols",
+                    dylib.name(),
+                    rela.r_offset(),
+                    rela.r_symbol(),
+                    count
+                )));
+            }
+        }
+    }
+    // 可执行的段只有在DT_TEXTREL时才会被临时设为可写
+    #[cfg(feature = "deny-textrel")]
+    if dyn_flags.textrel {
+        return Err(crate::textrel_error(format!(
+            "[{}] has relocations against read-only segments (DT_TEXTREL), which are refused by the deny-textrel feature",
+            dylib.name()
+        )));
+    }
+    #[cfg(not(feature = "std"))]
+    if dyn_flags.textrel {
+        log::warn!(
+            "Dylib [{}] has relocations against read-only segments (DT_TEXTREL)",
+            dylib.name()
+        );
+    }
+    let lib = ElfLibrary {
+        dylib,
+        flags,
+        textrel: dyn_flags.textrel,
+    };
     Ok(lib)
 }
 
@@ -214,8 +560,61 @@ impl ElfLibrary {
         flags: OpenFlags,
     ) -> Result<ElfLibrary> {
         use elf_loader::object;
-        let file = object::ElfFile::new(path.as_ref(), file);
-        from_impl(file, flags)
+        let path = path.as_ref();
+        let size = file.metadata()?.len() as usize;
+        let file = object::ElfFile::new(path, file);
+        from_impl(file, size, flags, Some(origin_of(path)))
+    }
+
+    /// Load a elf dynamic library by a path relative to a directory file descriptor, it uses `openat` under the hood.
+    ///
+    /// `dir_fd` can be opened with `O_PATH`, so a host can pin an entire plugin directory by fd and
+    /// the library is still found even if the directory is renamed or the path is replaced in the meantime.
+    /// The `flags` argument can control how dynamic libraries are loaded.
+    ///
+    /// # Note
+    /// `O_PATH` is only available on Linux (2.6.39 or later). On other unix systems `dir_fd` must be opened with `O_RDONLY`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// # use std::os::unix::fs::OpenOptionsExt;
+    /// let dir = std::fs::OpenOptions::new()
+    ///     .read(true)
+    ///     .custom_flags(libc::O_PATH | libc::O_DIRECTORY)
+    ///     .open("/path/to/plugins")
+    ///     .unwrap();
+    /// let lib = ElfLibrary::from_path_fd(&dir, "awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_path_fd(
+        dir_fd: impl std::os::fd::AsFd,
+        relative_name: impl AsRef<str>,
+        flags: OpenFlags,
+    ) -> Result<Self> {
+        use std::os::fd::{AsRawFd, FromRawFd};
+        let name = relative_name.as_ref();
+        let cname = std::ffi::CString::new(name)
+            .map_err(|_| find_lib_error(format!("invalid library name: {}", name)))?;
+        let fd = unsafe {
+            libc::openat(
+                dir_fd.as_fd().as_raw_fd(),
+                cname.as_ptr(),
+                libc::O_RDONLY | libc::O_CLOEXEC,
+            )
+        };
+        if fd < 0 {
+            return Err(std::io::Error::last_os_error().into());
+        }
+        let file = unsafe { std::fs::File::from_raw_fd(fd) };
+        let size = file.metadata()?.len() as usize;
+        // 无法得到dir_fd对应的目录，因此origin为None
+        from_impl(
+            elf_loader::object::ElfFile::new(name, file),
+            size,
+            flags,
+            None,
+        )
     }
 
     /// Load a elf dynamic library from bytes.
@@ -234,11 +633,13 @@ impl ElfLibrary {
         path: impl AsRef<str>,
         flags: OpenFlags,
     ) -> Result<Self> {
-        let file = ElfBinary::new(path.as_ref(), bytes.as_ref());
-        from_impl(file, flags)
+        let bytes = bytes.as_ref();
+        let file = ElfBinary::new(path.as_ref(), bytes);
+        from_impl(file, bytes.len(), flags, None)
     }
 
-    /// Load an existing dynamic library using the shortname of the library
+    /// Load an existing dynamic library using the shortname of the library,
+    /// which is its `DT_SONAME` if it has one, otherwise the last component of its path
     /// # Examples
     /// ```no_run
     /// # use ::dlopen_rs::ElfLibrary;
@@ -250,7 +651,7 @@ impl ElfLibrary {
             .all
             .get(shortname)
             .filter(|lib| lib.deps().is_some())
-            .map(|lib| lib.get_dylib())
+            .map(|lib| lib.get_dylib(&MANAGER))
             .ok_or(find_lib_error(format!("{}: load fail", shortname)))
     }
 
@@ -264,46 +665,140 @@ impl ElfLibrary {
         self.dylib.name()
     }
 
+    /// Make the text segments writable until the returned guard is dropped if the library has `DT_TEXTREL`.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub(crate) fn textrel_guard(&self) -> Option<textrel::TextRelGuard> {
+        self.textrel
+            .then(|| textrel::TextRelGuard::new(unsafe { self.dylib.core_component() }))
+    }
+
+    #[cfg(not(feature = "std"))]
+    #[inline]
+    pub(crate) fn textrel_guard(&self) -> Option<()> {
+        None
+    }
+
     fn relocate_impl<'a, F>(self, libs: &[Dylib<'a>], find: &F) -> Result<Dylib<'a>>
+    where
+        F: for<'b> Fn(&'b str) -> Option<*const ()>,
+    {
+        self.relocate_deps(libs.iter().map(|lib| lib.inner.clone()), find)
+    }
+
+    fn relocate_deps<'a, F>(
+        self,
+        libs: impl Iterator<Item = CoreComponent>,
+        find: &F,
+    ) -> Result<Dylib<'a>>
     where
         F: for<'b> Fn(&'b str) -> Option<*const ()>,
     {
         let mut deps = Vec::new();
         deps.push(unsafe { self.dylib.core_component() });
-        deps.extend(libs.iter().map(|lib| lib.inner.clone()));
+        deps.extend(libs);
         let deps = Arc::new(deps.into_boxed_slice());
         let lazy_scope = create_lazy_scope(&deps, self.dylib.is_lazy());
+        let _textrel = self.textrel_guard();
+        // 预加载的库优先级最高，但是CUSTOM_NOT_REGISTER不访问全局变量
+        let preloaded: Vec<CoreComponent> = if self.flags.contains(OpenFlags::CUSTOM_NOT_REGISTER) {
+            Vec::new()
+        } else {
+            let reader = MANAGER.read();
+            reader
+                .global
+                .values()
+                .take(reader.preloaded)
+                .cloned()
+                .collect()
+        };
+        let from = String::from(self.name());
+        let strong = StrongScope::new(preloaded.iter().chain(deps.iter()));
+        let find_strong = find_strong(&strong, unsafe { self.dylib.core_component_ref() });
+        let find = |name: &str| {
+            audit::symbind(name, &from)
+                .or_else(|| find(name))
+                .or_else(|| find_strong(name))
+        };
         let core = self
             .dylib
             .relocate(
-                deps.clone().iter().map(|dep| dep),
-                find,
+                preloaded.iter().chain(deps.iter()),
+                &find,
                 deal_unknown,
                 lazy_scope,
             )?
             .into_core_component();
+        unsafe { symbolic::add_addends(&core) };
+        emit(LoadEvent::Relocated { name: core.name() });
+        deferred::init(&core, self.flags);
         if !self.flags.contains(OpenFlags::CUSTOM_NOT_REGISTER) {
             register(
                 core.clone(),
                 self.flags,
                 Some(deps.clone()),
                 &mut MANAGER.write(),
-                false,
-                None,
             );
-            Ok(Dylib {
-                inner: core,
-                flags: self.flags,
-                deps: Some(deps),
-                _marker: PhantomData,
-            })
+        }
+        let dylib = Dylib {
+            inner: core,
+            flags: self.flags,
+            deps: Some(deps),
+            manager: &MANAGER,
+            _marker: PhantomData,
+        };
+        audit::objopen(&dylib);
+        Ok(dylib)
+    }
+
+    /// Check that every symbol needed by the library can be resolved by `libs`, the builtin symbols and
+    /// the global scope, without relocating the library. The init and fini functions are never called.
+    ///
+    /// The error lists all unresolved symbols. Weak undefined symbols are allowed to be unresolved.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use ::dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let libc = ElfLibrary::load_existing("libc.so.6").unwrap();
+    /// ElfLibrary::from_file("/path/to/awesome.module", OpenFlags::RTLD_LOCAL)
+    ///     .unwrap()
+    ///     .verify(&[libc])
+    ///     .unwrap();
+    /// ```
+    pub fn verify<'a>(self, libs: impl AsRef<[Dylib<'a>]>) -> Result<()> {
+        let libs = libs.as_ref();
+        let symtab = self.dylib.symtab();
+        let core = unsafe { self.dylib.core_component() };
+        let global = MANAGER.read();
+        let mut missing: Vec<String> = Vec::new();
+        for rela in unsafe { histogram::relocations(core.base(), phdrs(&core)) } {
+            let r_sym = rela.r_symbol();
+            if r_sym == 0 {
+                continue;
+            }
+            let (dynsym, syminfo) = symtab.symbol_idx(r_sym);
+            if dynsym.st_shndx != SHN_UNDEF || dynsym.st_info >> 4 == STB_WEAK {
+                continue;
+            }
+            let name = syminfo.symbol_name();
+            let found = builtin::find(name).is_some()
+                || libs
+                    .iter()
+                    .map(|lib| &lib.inner)
+                    .chain(global.global.values())
+                    .any(|lib| lib.symtab().lookup_filter(&syminfo).is_some());
+            if !found && !missing.iter().any(|missing| missing == name) {
+                missing.push(String::from(name));
+            }
+        }
+        if missing.is_empty() {
+            Ok(())
         } else {
-            Ok(Dylib {
-                inner: core,
-                flags: self.flags,
-                deps: Some(deps),
-                _marker: PhantomData,
-            })
+            Err(find_symbol_error(format!(
+                "[{}] has unresolved symbols: {}",
+                self.name(),
+                missing.join(", ")
+            )))
         }
     }
 
@@ -319,7 +814,64 @@ impl ElfLibrary {
     /// ```
     #[inline]
     pub fn relocate<'a>(self, libs: impl AsRef<[Dylib<'a>]>) -> Result<Dylib<'a>> {
-        self.relocate_impl(libs.as_ref(), &|name| builtin::BUILTIN.get(name).copied())
+        self.relocate_impl(libs.as_ref(), &builtin::find)
+    }
+
+    /// Use libraries to relocate the current library, calling `progress(done, total)` periodically.
+    ///
+    /// `total` is the number of entries in `.rela.dyn` and `.rela.plt`, and `done` is the number of them processed so far.
+    /// The relocations are processed in order, and `done` is updated whenever a relocation resolves a symbol, so it advances
+    /// over the relocations that don't resolve symbols (e.g. relative relocations) in steps. `progress` is called with
+    /// `done == total` once the relocation succeeds.
+    /// # Examples
+    /// ```no_run
+    /// # use ::dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let libc = ElfLibrary::load_existing("libc.so.6").unwrap();
+    /// let lib = ElfLibrary::from_file("/path/to/awesome.module", OpenFlags::RTLD_NOW)
+    ///     .unwrap()
+    ///     .relocate_with_progress(&[libc], Some(&|done, total| println!("{}/{}", done, total)))
+    ///     .unwrap();
+    /// ```
+    pub fn relocate_with_progress<'a>(
+        self,
+        libs: impl AsRef<[Dylib<'a>]>,
+        progress: Option<&dyn Fn(usize, usize)>,
+    ) -> Result<Dylib<'a>> {
+        const STEP: usize = 256;
+        let Some(progress) = progress else {
+            return self.relocate(libs);
+        };
+        let is_lazy = self.dylib.is_lazy();
+        // 只读取重定位表，不会使用未重定位的库中的符号
+        let core = unsafe { self.dylib.core_component_ref() };
+        // elf_loader按顺序处理重定位项，并为每个需要符号的重定位项调用一次find，
+        // 所以第k次调用find时，已经处理的重定位项数就是第k个需要符号的重定位项的位置
+        let mut total = 0;
+        let mut positions = Vec::new();
+        for rela in unsafe { histogram::relocations(core.base(), phdrs(core)) } {
+            total += 1;
+            let r_type = rela.r_type() as u32;
+            if r_type == REL_GOT || r_type == REL_SYMBOLIC || (r_type == REL_JUMP_SLOT && !is_lazy)
+            {
+                positions.push(total);
+            }
+        }
+        let calls = core::cell::Cell::new(0);
+        let last = core::cell::Cell::new(0);
+        let find = |name: &str| {
+            let idx = calls.get();
+            calls.set(idx + 1);
+            if let Some(&done) = positions.get(idx) {
+                if done / STEP != last.get() / STEP {
+                    last.set(done);
+                    progress(done, total);
+                }
+            }
+            builtin::find(name)
+        };
+        let lib = self.relocate_impl(libs.as_ref(), &find)?;
+        progress(total, total);
+        Ok(lib)
     }
 
     /// Use libraries and function closure to relocate the current library.
@@ -355,7 +907,7 @@ impl ElfLibrary {
     where
         F: for<'b> Fn(&'b str) -> Option<*const ()>,
     {
-        let find = |name: &str| func(name).or(builtin::BUILTIN.get(name).copied());
+        let find = |name: &str| func(name).or(builtin::find(name));
         self.relocate_impl(libs.as_ref(), &find)
     }
 }
@@ -366,6 +918,7 @@ pub struct Dylib<'scope> {
     pub(crate) inner: CoreComponent,
     pub(crate) flags: OpenFlags,
     pub(crate) deps: Option<Arc<Box<[CoreComponent]>>>,
+    pub(crate) manager: &'static RwLock<Manager>,
     pub(crate) _marker: PhantomData<&'scope ()>,
 }
 
@@ -378,6 +931,24 @@ impl Debug for Dylib<'_> {
     }
 }
 
+/// Two handles are equal if they refer to the same loaded instance of a library, i.e. they have the same base address,
+/// even if they were obtained separately (e.g. from two calls to `dlopen`). The flags of the handles are not compared.
+impl PartialEq for Dylib<'_> {
+    #[inline]
+    fn eq(&self, other: &Self) -> bool {
+        self.base() == other.base()
+    }
+}
+
+impl Eq for Dylib<'_> {}
+
+impl core::hash::Hash for Dylib<'_> {
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.base().hash(state);
+    }
+}
+
 impl<'scope> Dylib<'scope> {
     /// Get the name of the dynamic library.
     #[inline]
@@ -391,16 +962,265 @@ impl<'scope> Dylib<'scope> {
         self.inner.cname()
     }
 
+    /// Get the id of the link-map namespace that the dynamic library belongs to.
+    #[inline]
+    pub fn namespace(&self) -> Lmid {
+        self.manager.read().nsid
+    }
+
     /// Get the base address of the dynamic library.
     #[inline]
     pub fn base(&self) -> usize {
         self.inner.base()
     }
 
+    /// Whether the dynamic library was relocated with lazy binding, so some of its functions may be resolved
+    /// on their first call instead of during relocation.
+    ///
+    /// It is `true` if the library was loaded with `RTLD_LAZY` and is not linked with `-z now`
+    /// (`DF_BIND_NOW`/`DF_1_NOW`), and `false` for `RTLD_NOW`. Calling [`Dylib::get`] does not resolve the
+    /// lazy slots; they are filled in when the functions are called. It is always `false` for the libraries
+    /// loaded by the program itself.
+    #[inline]
+    pub fn is_lazy(&self) -> bool {
+        self.inner
+            .user_data()
+            .get(LAZY_ID)
+            .and_then(|data| data.downcast_ref::<AtomicBool>())
+            .is_some_and(|lazy| lazy.load(Ordering::Acquire))
+    }
+
     /// Get the program headers of the dynamic library.
     #[inline]
     pub fn phdrs(&self) -> &[Phdr] {
-        self.inner.phdrs()
+        phdrs(&self.inner)
+    }
+
+    /// Get the directory that the dynamic library was loaded from, which is what `$ORIGIN` expands to.
+    /// It returns `None` for libraries loaded from bytes or by [`ElfLibrary::from_path_fd`],
+    /// and for the libraries loaded by the program itself.
+    #[cfg(feature = "std")]
+    #[inline]
+    pub fn origin(&self) -> Option<&std::path::Path> {
+        self.inner
+            .user_data()
+            .get(ORIGIN_ID)
+            .and_then(|data| data.downcast_ref::<String>())
+            .map(std::path::Path::new)
+    }
+
+    /// Get the `PT_DYNAMIC` program header of the dynamic library.
+    #[inline]
+    pub fn dynamic_phdr(&self) -> Option<&Phdr> {
+        self.find_phdr(PT_DYNAMIC)
+    }
+
+    /// Get the raw `(d_tag, d_un)` entries of the dynamic section, without the terminating `DT_NULL`.
+    ///
+    /// The pointers (such as `DT_STRTAB`) are relative to the base address, except for the libraries loaded
+    /// by the program itself, which have been adjusted by the system dynamic linker. The entries are reported with
+    /// their original tags, although `DT_INIT`, `DT_INIT_ARRAY` and `DT_RELR` are hidden from elf_loader in memory.
+    pub fn dynamic_entries(&self) -> Vec<(i64, u64)> {
+        let Some(dynamic) = self.dynamic_phdr() else {
+            return Vec::new();
+        };
+        let hidden = self
+            .inner
+            .user_data()
+            .get(HIDDEN_TAGS_ID)
+            .and_then(|data| data.downcast_ref::<HiddenTags>());
+        let mut entries = Vec::new();
+        let mut cur = (self.base() + dynamic.p_vaddr as usize) as *const Dyn;
+        unsafe {
+            while (*cur).d_tag != DT_NULL {
+                let idx = entries.len();
+                let tag = hidden
+                    .and_then(|hidden| hidden.iter().find(|(i, _)| *i == idx))
+                    .map_or((*cur).d_tag, |(_, tag)| *tag);
+                entries.push((tag, (*cur).d_un));
+                cur = cur.add(1);
+            }
+        }
+        entries
+    }
+
+    /// Get the `PT_TLS` program header of the dynamic library.
+    #[inline]
+    pub fn tls_phdr(&self) -> Option<&Phdr> {
+        self.find_phdr(PT_TLS)
+    }
+
+    /// Get the `PT_GNU_EH_FRAME` program header of the dynamic library.
+    #[inline]
+    pub fn eh_frame_phdr(&self) -> Option<&Phdr> {
+        self.find_phdr(PT_GNU_EH_FRAME)
+    }
+
+    /// Get the address range of the `.eh_frame_hdr` section (the `PT_GNU_EH_FRAME` segment) in memory.
+    /// A host that loads libraries with `OpenFlags::CUSTOM_SKIP_UNWIND_REGISTER` can use it to register
+    /// the unwind information with its own unwinder.
+    #[inline]
+    pub fn eh_frame_range(&self) -> Option<Range<usize>> {
+        self.eh_frame_phdr().map(|phdr| {
+            let start = self.base() + phdr.p_vaddr as usize;
+            start..start + phdr.p_memsz as usize
+        })
+    }
+
+    /// Get the addresses of the `.eh_frame` and `.eh_frame_hdr` sections in memory, in that order. The `.eh_frame` address is
+    /// decoded from the `eh_frame_ptr` field of `.eh_frame_hdr`, whose range is given by [`Dylib::eh_frame_range`].
+    ///
+    /// A host with its own unwinder can register the frames with it, e.g. `__register_frame` takes the `.eh_frame` address.
+    /// It does not change the registration done by dlopen-rs, see `OpenFlags::CUSTOM_SKIP_UNWIND_REGISTER`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::CUSTOM_SKIP_UNWIND_REGISTER).unwrap();
+    /// if let Some((eh_frame, eh_frame_hdr)) = lib.eh_frame() {
+    ///     println!("eh_frame: 0x{:x}, eh_frame_hdr: 0x{:x}", eh_frame, eh_frame_hdr);
+    /// }
+    /// ```
+    pub fn eh_frame(&self) -> Option<(usize, usize)> {
+        let eh_frame_hdr = self.eh_frame_range()?.start;
+        let eh_frame = unsafe { ehframe::eh_frame_ptr(eh_frame_hdr) }?;
+        Some((eh_frame, eh_frame_hdr))
+    }
+
+    /// Get the `PT_LOAD` program headers of the dynamic library.
+    #[inline]
+    pub fn segments(&self) -> impl Iterator<Item = &Phdr> {
+        self.phdrs().iter().filter(|phdr| phdr.p_type == PT_LOAD)
+    }
+
+    /// Get the address range of the code of the dynamic library in memory, i.e. the executable `PT_LOAD` segment.
+    /// If there are several executable segments, the range covers all of them.
+    #[inline]
+    pub fn text_range(&self) -> Range<usize> {
+        let base = self.base();
+        self.segments()
+            .filter(|phdr| phdr.p_flags & PF_X != 0)
+            .map(|phdr| {
+                let start = base + phdr.p_vaddr as usize;
+                start..start + phdr.p_memsz as usize
+            })
+            .reduce(|a, b| a.start.min(b.start)..a.end.max(b.end))
+            .unwrap_or(base..base)
+    }
+
+    /// Write a line in the format of `/tmp/perf-<pid>.map` for each function defined in the dynamic library,
+    /// i.e. the address and the size in hex followed by the name, so that `perf` can symbolicate the samples
+    /// in the code of the libraries loaded by dlopen-rs.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let mut map = std::fs::OpenOptions::new()
+    ///     .create(true)
+    ///     .append(true)
+    ///     .open(format!("/tmp/perf-{}.map", std::process::id()))
+    ///     .unwrap();
+    /// lib.write_perf_map_entry(&mut map).unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_perf_map_entry(&self, w: &mut impl std::io::Write) -> std::io::Result<()> {
+        let mut res = Ok(());
+        unsafe {
+            symkind::for_each_func(self.base(), self.phdrs(), |name, addr, size| {
+                if res.is_ok() {
+                    res = writeln!(w, "{:x} {:x} {}", addr, size, name.to_string_lossy());
+                }
+            })
+        };
+        res
+    }
+
+    #[inline]
+    fn find_phdr(&self, p_type: u32) -> Option<&Phdr> {
+        self.phdrs().iter().find(|phdr| phdr.p_type == p_type)
+    }
+
+    /// Get the sections of the dynamic library, read from the section header table of the file when it was loaded.
+    ///
+    /// It is empty for the libraries loaded by the program itself (see `init`), and for the files whose
+    /// section headers were stripped or could not be read.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let text = lib.sections().iter().find(|section| section.name == ".text").unwrap();
+    /// println!(".text: 0x{:x} {} bytes", lib.base() + text.offset.unwrap(), text.size);
+    /// ```
+    #[cfg(feature = "sections")]
+    pub fn sections(&self) -> &[Section] {
+        self.inner
+            .user_data()
+            .get(SECTIONS_ID)
+            .and_then(|data| data.downcast_ref::<Vec<Section>>())
+            .map_or(&[], |sections| sections.as_slice())
+    }
+
+    /// Get the operating system and the minimum kernel version `[major, minor, patch]` that the dynamic library
+    /// was built for, which are recorded in its `NT_GNU_ABI_TAG` note. It is `None` if the library has no such note.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags, OsAbi};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// if let Some((OsAbi::Linux, [major, minor, _])) = lib.abi_tag() {
+    ///     assert!((major, minor) <= (5, 4), "the plugin needs a newer kernel");
+    /// }
+    /// ```
+    #[inline]
+    pub fn abi_tag(&self) -> Option<(OsAbi, [u32; 3])> {
+        unsafe { note::abi_tag(self.base(), self.phdrs()) }
+    }
+
+    /// Whether the dynamic library requests an executable stack, i.e. its `PT_GNU_STACK` segment has `PF_X`.
+    ///
+    /// dlopen-rs can not make the stacks of the running threads executable, so such a library may fault when it
+    /// uses trampolines on the stack, e.g. for GCC nested functions. The host can reject the library or run it
+    /// in a thread whose stack is executable.
+    #[inline]
+    pub fn wants_exec_stack(&self) -> bool {
+        self.find_phdr(PT_GNU_STACK)
+            .is_some_and(|phdr| phdr.p_flags & PF_X != 0)
+    }
+
+    /// Get the path of the dynamic linker that the dynamic library was built for, which is recorded in its `PT_INTERP` segment,
+    /// e.g. `/lib64/ld-linux-x86-64.so.2` for glibc or `/lib/ld-musl-x86_64.so.1` for musl.
+    ///
+    /// dlopen-rs does not use the interpreter, but it tells which libc the library expects. Most shared libraries
+    /// have no `PT_INTERP`, in which case it is `None`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// if lib.interpreter().is_some_and(|interp| interp.contains("musl")) {
+    ///     println!("[{}] was built against musl", lib.name());
+    /// }
+    /// ```
+    pub fn interpreter(&self) -> Option<&str> {
+        let phdr = self.find_phdr(PT_INTERP)?;
+        let bytes = unsafe {
+            core::slice::from_raw_parts(
+                (self.base() + phdr.p_vaddr as usize) as *const u8,
+                phdr.p_filesz as usize,
+            )
+        };
+        CStr::from_bytes_until_nul(bytes).ok()?.to_str().ok()
+    }
+
+    /// Get the cpu features required by the dynamic library, which are recorded in its `PT_GNU_PROPERTY` segment.
+    ///
+    /// On a CET-enabled host, a library without `X86_IBT` or `X86_SHSTK` may trap, so the host can use this
+    /// to decide whether to use the library.
+    #[inline]
+    pub fn cpu_features(&self) -> CpuFeatures {
+        unsafe { property::cpu_features(self.base(), self.phdrs()) }
     }
 
     /// Get the needed libs' name of the elf object.
@@ -409,6 +1229,36 @@ impl<'scope> Dylib<'scope> {
         self.inner.needed_libs()
     }
 
+    /// Get the needed libs' name of the elf object together with the path of the file that each of them resolved to.
+    ///
+    /// The path is `None` if the dependency is not among the libraries loaded with this one,
+    /// e.g. the library was loaded with `relocate` and the symbols were provided by other means.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/libplugin.so", OpenFlags::RTLD_NOW).unwrap();
+    /// for (name, path) in lib.needed_libs_resolved() {
+    ///     println!("{} => {:?}", name, path);
+    /// }
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn needed_libs_resolved(&self) -> Vec<(&str, Option<std::path::PathBuf>)> {
+        let deps = self.deps.as_deref().map_or(&[][..], |deps| &deps[..]);
+        self.needed_libs()
+            .iter()
+            .map(|name| {
+                // DT_NEEDED中的名字可能是库的DT_SONAME，也可能是其文件名
+                let path = deps
+                    .iter()
+                    .skip(1)
+                    .find(|dep| registry_name(dep) == *name || dep.shortname() == *name)
+                    .map(|dep| std::path::PathBuf::from(dep.name()));
+                (*name, path)
+            })
+            .collect()
+    }
+
     /// Get a pointer to a function or static variable by symbol name.
     ///
     /// The symbol is interpreted as-is; no mangling is done. This means that symbols like `x::y` are
@@ -432,11 +1282,360 @@ impl<'scope> Dylib<'scope> {
     ///     **awesome_variable = 42.0;
     /// };
     /// ```
+    /// # Note
+    /// The search scope starts with the library itself and then follows its dependencies,
+    /// so a symbol exported by the library is always preferred over one with the same name in a dependency.
+    /// With the `version` feature, the default version (`@@`) of a versioned symbol is returned like glibc does;
+    /// without it, the first definition in the symbol table is returned.
     #[inline]
     pub unsafe fn get<'lib, T>(&'lib self, name: &str) -> Result<Symbol<'lib, T>> {
         find_symbol(self.deps.as_ref().unwrap(), name)
     }
 
+    /// Get the address of a function or static variable by the raw bytes of its name, which do not need to be valid UTF-8.
+    ///
+    /// The search scope is the same as [`Dylib::get`]. It returns the raw address like `Symbol::into_raw`, which is only
+    /// valid as long as the library is loaded. Names that are not valid UTF-8 are looked up by bytes in the `.dynsym`
+    /// of each library, which does not resolve `STT_GNU_IFUNC` symbols.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// # use std::ffi::CStr;
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let name: &CStr = c"awesome_function";
+    /// let ptr = lib.get_bytes(name.to_bytes()).unwrap();
+    /// let awesome_function: extern "C" fn(f64) -> f64 = unsafe { core::mem::transmute(ptr) };
+    /// awesome_function(0.42);
+    /// ```
+    pub fn get_bytes(&self, name: &[u8]) -> Result<*const ()> {
+        find_symbol_bytes(self.deps.as_ref().unwrap().iter(), name).ok_or(find_symbol_error(
+            format!("can not find symbol:{}", name.escape_ascii()),
+        ))
+    }
+
+    /// Get a pointer to a function or static variable by symbol name, searching the library itself and then `scope` in order.
+    ///
+    /// Unlike [`Dylib::get`], the dependencies of the library are not searched, so the host decides which symbols
+    /// are visible regardless of how the library was relocated.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::CUSTOM_NOT_REGISTER).unwrap();
+    /// let scope = [ElfLibrary::dlopen("/path/to/extension.module", OpenFlags::CUSTOM_NOT_REGISTER).unwrap()];
+    /// let hook = unsafe { lib.get_in::<extern "C" fn()>("hook", &scope).unwrap() };
+    /// hook();
+    /// ```
+    pub unsafe fn get_in<'lib, T>(
+        &'lib self,
+        name: &str,
+        scope: &'lib [Dylib],
+    ) -> Result<Symbol<'lib, T>> {
+        log::info!("Get the symbol [{}] in [{}]", name, self.inner.shortname());
+        core::iter::once(&self.inner)
+            .chain(scope.iter().map(|lib| &lib.inner))
+            .find_map(|lib| lib.get::<T>(name))
+            .ok_or(find_symbol_error(format!("can not find symbol:{}", name)))
+    }
+
+    /// Get a pointer to a function or static variable by symbol name, together with the type and the size (`st_size`)
+    /// of the symbol. The search scope is the same as [`Dylib::get`].
+    ///
+    /// It can be used to check that a symbol is really a function before calling it, or that a data symbol is large enough.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags, SymKind};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let (table, kind, size) = unsafe { lib.get_typed::<*const u32>("table").unwrap() };
+    /// assert_eq!(kind, SymKind::Object);
+    /// assert!(size >= 16 * size_of::<u32>());
+    /// ```
+    pub unsafe fn get_typed<'lib, T>(
+        &'lib self,
+        name: &str,
+    ) -> Result<(Symbol<'lib, T>, SymKind, usize)> {
+        log::info!("Get the symbol [{}] in [{}]", name, self.inner.shortname());
+        self.deps
+            .as_ref()
+            .unwrap()
+            .iter()
+            .find_map(|lib| {
+                let sym = lib.get::<T>(name)?;
+                let (kind, size) =
+                    symkind::lookup(lib.base(), phdrs(lib), name).unwrap_or((SymKind::NoType, 0));
+                Some((sym, kind, size))
+            })
+            .ok_or(find_symbol_error(format!("can not find symbol:{}", name)))
+    }
+
+    /// Whether the library itself defines (exports) the symbol `name` in its `.dynsym`, rather than importing it
+    /// from a dependency. Unlike [`Dylib::get`], the dependencies are not searched.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// if lib.defines("plugin_entry") {
+    ///     let entry = unsafe { lib.get::<extern "C" fn()>("plugin_entry").unwrap() };
+    ///     entry();
+    /// }
+    /// ```
+    #[inline]
+    pub fn defines(&self, name: &str) -> bool {
+        unsafe { symkind::lookup(self.base(), self.phdrs(), name) }.is_some()
+    }
+
+    /// Get the offset of the symbol `name` defined by this library itself, which is the `st_value` in its `.dynsym`
+    /// before the base address is added. Together with [`Dylib::base`], it maps a symbol in the file to its runtime address.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let offset = lib.symbol_offset("run").unwrap();
+    /// println!("break *0x{:x}", lib.base() + offset as usize);
+    /// ```
+    pub fn symbol_offset(&self, name: &str) -> Option<u64> {
+        unsafe { symkind::offset(self.base(), self.phdrs(), name) }
+    }
+
+    /// Get the addresses of several symbols at once, walking the dependencies of the library only once.
+    ///
+    /// The search scope is the same as [`Dylib::get`]. If some symbols can not be found, the error lists all of them.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let [init, run] = lib.get_many(["plugin_init", "plugin_run"]).unwrap();
+    /// let init: extern "C" fn() = unsafe { core::mem::transmute(init) };
+    /// init();
+    /// ```
+    pub fn get_many<const N: usize>(&self, names: [&str; N]) -> Result<[*const (); N]> {
+        let mut syms = [core::ptr::null::<()>(); N];
+        let mut remaining = N;
+        for lib in self.deps.as_ref().unwrap().iter() {
+            for (name, sym) in names.iter().zip(syms.iter_mut()) {
+                if sym.is_null() {
+                    if let Some(found) = unsafe { lib.get::<()>(name) } {
+                        *sym = found.into_raw();
+                        remaining -= 1;
+                    }
+                }
+            }
+            if remaining == 0 {
+                return Ok(syms);
+            }
+        }
+        let missing: Vec<&str> = names
+            .iter()
+            .zip(syms.iter())
+            .filter(|(_, sym)| sym.is_null())
+            .map(|(name, _)| *name)
+            .collect();
+        Err(find_symbol_error(format!(
+            "can not find symbols:{}",
+            missing.join(", ")
+        )))
+    }
+
+    /// Get a pointer to a function or static variable by symbol name, only searching the symbols
+    /// exported by the dynamic library itself. The dependencies of the library are not searched.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let version = unsafe { lib.get_own::<extern "C" fn() -> u32>("version").unwrap() };
+    /// version();
+    /// ```
+    #[inline]
+    pub unsafe fn get_own<'lib, T>(&'lib self, name: &str) -> Result<Symbol<'lib, T>> {
+        log::info!("Get the symbol [{}] in [{}]", name, self.inner.shortname());
+        self.inner
+            .get::<T>(name)
+            .ok_or(find_symbol_error(format!("can not find symbol:{}", name)))
+    }
+
+    /// Run the init functions (`DT_INIT` and `DT_INIT_ARRAY`) of the dynamic library and its dependencies
+    /// that were loaded with `OpenFlags::CUSTOM_DEFER_INIT`.
+    ///
+    /// Dependencies are initialized before the library itself. The init functions of each library are
+    /// called at most once, so calling this function again (or from another `Dylib` sharing the library) does nothing.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL | OpenFlags::CUSTOM_DEFER_INIT).unwrap();
+    /// // set up the sandbox here
+    /// lib.run_init();
+    /// ```
+    pub fn run_init(&self) {
+        for core in self.deps.as_ref().unwrap().iter().rev() {
+            if let Some(deferred) = deferred::get(core) {
+                if unsafe { deferred.call_init(core.base()) } {
+                    log::debug!("Ran the deferred init functions of [{}]", core.name());
+                }
+            }
+        }
+    }
+
+    /// Get the number of relocation entries of the dynamic library, bucketed by relocation type.
+    ///
+    /// The buckets are `relative`, `glob_dat`, `jump_slot`, `symbolic`, `tls`, `irelative`, `copy` and `other`,
+    /// and only the non-empty ones are present. Relative relocations are the cheapest to apply, so a library with
+    /// a large number of symbolic relocations may benefit from being linked with `-Bsymbolic`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// for (ty, count) in lib.relocation_histogram() {
+    ///     println!("{}: {}", ty, count);
+    /// }
+    /// ```
+    pub fn relocation_histogram(&self) -> BTreeMap<&'static str, usize> {
+        unsafe { histogram::relocation_histogram(self.base(), self.phdrs()) }
+    }
+
+    /// Get the number of strong references to the dynamic library.
+    ///
+    /// The count consists of every `Dylib` (and `OwnedSymbol`) of the library, the dependency list of each library
+    /// depending on it (including its own dependency list), and the entries kept by dlopen-rs for a registered library:
+    /// one in the list of all libraries and one in the global scope if it was opened with `RTLD_GLOBAL`.
+    #[inline]
+    pub fn strong_count(&self) -> usize {
+        self.inner.strong_count()
+    }
+
+    /// Whether the dynamic library is still registered in dlopen-rs, so it is reused by `dlopen` and `load_existing`.
+    /// It is always `false` for libraries loaded with `CUSTOM_NOT_REGISTER`.
+    #[inline]
+    pub fn is_resident(&self) -> bool {
+        self.manager
+            .read()
+            .all
+            .get(registry_name(&self.inner))
+            .is_some_and(|lib| lib.core_component_ref().base() == self.base())
+    }
+
+    /// Close the dynamic library explicitly instead of relying on `Drop`.
+    ///
+    /// It returns `true` if the library was unloaded: its fini functions were called, it was removed from
+    /// the libraries managed by dlopen-rs and its memory was unmapped. It returns `false` if the library is
+    /// still referenced, e.g. by another `Dylib`, a library depending on it or an `OwnedSymbol`.
+    ///
+    /// # Note
+    /// `close` does nothing for libraries opened with `RTLD_NODELETE`, and always returns `false` for them.
+    pub fn close(self) -> Result<bool> {
+        if self.flags.contains(OpenFlags::RTLD_NODELETE) {
+            log::debug!("Ignore closing [{}] with RTLD_NODELETE", self.name());
+            return Ok(false);
+        }
+        let weak = self.inner.downgrade();
+        drop(self);
+        if let Some(core) = weak.upgrade() {
+            log::debug!(
+                "[{}] is still alive, it has {} references",
+                core.shortname(),
+                core.strong_count() - 1
+            );
+            Ok(false)
+        } else {
+            Ok(true)
+        }
+    }
+
+    /// Load an updated version of the dynamic library from `new_path` and relocate it against the same
+    /// dependencies as `self`, without searching for them again.
+    ///
+    /// The new library is loaded with `CUSTOM_NOT_REGISTER`, so it does not replace `self` in the libraries
+    /// managed by dlopen-rs. `self` stays valid until it is dropped, so the caller can switch to the new
+    /// library and drop the old one once no symbol of it is in use.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use ::dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let mut lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_NOW).unwrap();
+    /// lib = lib.reload("/path/to/awesome.module.new").unwrap();
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn reload(&self, new_path: impl AsRef<std::ffi::OsStr>) -> Result<Dylib<'scope>> {
+        let flags = self.flags | OpenFlags::CUSTOM_NOT_REGISTER;
+        let lib = ElfLibrary::from_file(new_path, flags)?;
+        log::info!("Reloading [{}] from [{}]", self.name(), lib.name());
+        // 库本身位于deps的第一个位置
+        let deps = self
+            .deps
+            .iter()
+            .flat_map(|deps| deps.iter().skip(1))
+            .cloned();
+        lib.relocate_deps(deps, &builtin::find)
+    }
+
+    /// Get the TLS module id of the dynamic library, which is the `ti_module` passed to `__tls_get_addr`.
+    /// It returns `None` if the library has no `PT_TLS` segment.
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn tls_module_id(&self) -> Option<usize> {
+        self.tls().map(|tls| tls.module_id())
+    }
+
+    /// Get the initialization image of the TLS block (the `.tdata` bytes) of the dynamic library.
+    /// The rest of the block (`.tbss`) is zero-initialized.
+    /// It returns `None` if the library has no `PT_TLS` segment.
+    #[cfg(feature = "tls")]
+    #[inline]
+    pub fn tls_template(&self) -> Option<&[u8]> {
+        self.tls().map(|tls| tls.template())
+    }
+
+    /// Get the address of the thread local variable `name` of the dynamic library for the calling thread.
+    /// Each thread gets a different address, and the TLS block of the thread is allocated on the first access.
+    ///
+    /// It fails if `name` is not a `STT_TLS` symbol defined by the library itself, or if the library was
+    /// loaded by the program itself, whose TLS is managed by the system dynamic linker.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let counter = lib.get_tls::<u32>("counter").unwrap();
+    /// unsafe { *counter += 1 };
+    /// ```
+    #[cfg(feature = "tls")]
+    pub fn get_tls<T>(&self, name: &str) -> Result<*mut T> {
+        let not_found = || find_symbol_error(format!("can not find thread local symbol:{}", name));
+        let module_id = self.tls_module_id().ok_or_else(not_found)?;
+        let offset = unsafe { symkind::tls_offset(self.base(), self.phdrs(), name) }
+            .ok_or_else(not_found)?;
+        let addr = unsafe { tls::tls_get_addr(&tls::TlsIndex::new(module_id, offset)) };
+        if addr.is_null() {
+            return Err(not_found());
+        }
+        Ok(addr as *mut T)
+    }
+
+    #[cfg(feature = "tls")]
+    #[inline]
+    fn tls(&self) -> Option<&tls::ElfTls> {
+        self.inner
+            .user_data()
+            .get(TLS_ID)
+            .and_then(|data| data.downcast_ref::<tls::ElfTls>())
+    }
+
     /// Load a versioned symbol from the dynamic library.
     ///
     /// # Examples
@@ -455,3 +1654,65 @@ impl<'scope> Dylib<'scope> {
             .ok_or(find_symbol_error(format!("can not find symbol:{}", name)))
     }
 }
+
+impl Dylib<'static> {
+    /// Get a pointer to a function or static variable by symbol name, returning a handle that keeps
+    /// the dynamic library loaded instead of borrowing it.
+    ///
+    /// The returned [`OwnedSymbol`] is `'static`, so it can be moved into a thread that outlives the `Dylib`.
+    ///
+    /// # Safety
+    /// Users of this API must specify the correct type of the function or variable loaded.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// let work = unsafe { lib.get_owned::<extern "C" fn()>("work").unwrap() };
+    /// std::thread::spawn(move || work()).join().unwrap();
+    /// ```
+    pub unsafe fn get_owned<T>(&self, name: &str) -> Result<OwnedSymbol<T>> {
+        let ptr = self.get::<T>(name)?.into_raw();
+        Ok(OwnedSymbol {
+            ptr,
+            _lib: self.clone(),
+            _marker: PhantomData,
+        })
+    }
+}
+
+/// A symbol that keeps the dynamic library it comes from loaded, see [`Dylib::get_owned`].
+pub struct OwnedSymbol<T> {
+    ptr: *const (),
+    _lib: Dylib<'static>,
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for OwnedSymbol<T> {}
+unsafe impl<T: Sync> Sync for OwnedSymbol<T> {}
+
+impl<T> OwnedSymbol<T> {
+    /// Get the raw address of the symbol. It is valid as long as the `OwnedSymbol` is alive.
+    #[inline]
+    pub fn as_ptr(&self) -> *const () {
+        self.ptr
+    }
+}
+
+impl<T> Deref for OwnedSymbol<T> {
+    type Target = T;
+
+    #[inline]
+    fn deref(&self) -> &T {
+        unsafe { &*(&self.ptr as *const *const () as *const T) }
+    }
+}
+
+impl<T> Debug for OwnedSymbol<T> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("OwnedSymbol")
+            .field("ptr", &self.ptr)
+            .field("lib", &self._lib.name())
+            .finish()
+    }
+}
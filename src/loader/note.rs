@@ -0,0 +1,64 @@
+use elf_loader::arch::Phdr;
+
+const PT_NOTE: u32 = 4;
+const NT_GNU_ABI_TAG: u32 = 1;
+
+/// The operating system recorded in the `NT_GNU_ABI_TAG` note of a dynamic library, see `Dylib::abi_tag`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OsAbi {
+    /// `ELF_NOTE_OS_LINUX`
+    Linux,
+    /// `ELF_NOTE_OS_GNU`
+    Hurd,
+    /// `ELF_NOTE_OS_SOLARIS2`
+    Solaris,
+    /// `ELF_NOTE_OS_FREEBSD`
+    FreeBsd,
+    /// Any other value.
+    Other(u32),
+}
+
+impl OsAbi {
+    #[inline]
+    fn from_raw(os: u32) -> OsAbi {
+        match os {
+            0 => OsAbi::Linux,
+            1 => OsAbi::Hurd,
+            2 => OsAbi::Solaris,
+            3 => OsAbi::FreeBsd,
+            os => OsAbi::Other(os),
+        }
+    }
+}
+
+#[inline]
+fn align_up(val: usize, align: usize) -> usize {
+    (val + align - 1) & !(align - 1)
+}
+
+/// Find the `NT_GNU_ABI_TAG` note in the `PT_NOTE` segments of a mapped library.
+pub(crate) unsafe fn abi_tag(base: usize, phdrs: &[Phdr]) -> Option<(OsAbi, [u32; 3])> {
+    let read_u32 = |addr: usize| (addr as *const u32).read_unaligned();
+    for phdr in phdrs.iter().filter(|phdr| phdr.p_type == PT_NOTE) {
+        let start = base + phdr.p_vaddr as usize;
+        let end = start + phdr.p_memsz as usize;
+        let align = phdr.p_align.max(4) as usize;
+        let mut note = start;
+        // 每个note由namesz、descsz、type、name和desc组成
+        while note + 12 <= end {
+            let namesz = read_u32(note) as usize;
+            let descsz = read_u32(note + 4) as usize;
+            let ty = read_u32(note + 8);
+            let name = core::slice::from_raw_parts((note + 12) as *const u8, namesz);
+            let desc = note + 12 + align_up(namesz, align);
+            if ty == NT_GNU_ABI_TAG && name == b"GNU\0" && descsz >= 16 && desc + 16 <= end {
+                return Some((
+                    OsAbi::from_raw(read_u32(desc)),
+                    [read_u32(desc + 4), read_u32(desc + 8), read_u32(desc + 12)],
+                ));
+            }
+            note = desc + align_up(descsz, align);
+        }
+    }
+    None
+}
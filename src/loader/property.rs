@@ -0,0 +1,68 @@
+use bitflags::bitflags;
+use elf_loader::arch::Phdr;
+
+const PT_GNU_PROPERTY: u32 = 0x6474e553;
+const NT_GNU_PROPERTY_TYPE_0: u32 = 5;
+const GNU_PROPERTY_AARCH64_FEATURE_1_AND: u32 = 0xc0000000;
+const GNU_PROPERTY_X86_FEATURE_1_AND: u32 = 0xc0000002;
+
+bitflags! {
+    /// The cpu features required by a dynamic library, recorded in its `PT_GNU_PROPERTY` segment.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct CpuFeatures: u32 {
+        /// x86 Indirect Branch Tracking (`GNU_PROPERTY_X86_FEATURE_1_IBT`)
+        const X86_IBT = 1 << 0;
+        /// x86 Shadow Stack (`GNU_PROPERTY_X86_FEATURE_1_SHSTK`)
+        const X86_SHSTK = 1 << 1;
+        /// AArch64 Branch Target Identification (`GNU_PROPERTY_AARCH64_FEATURE_1_BTI`)
+        const AARCH64_BTI = 1 << 2;
+        /// AArch64 Pointer Authentication (`GNU_PROPERTY_AARCH64_FEATURE_1_PAC`)
+        const AARCH64_PAC = 1 << 3;
+    }
+}
+
+#[inline]
+fn align_up(val: usize, align: usize) -> usize {
+    (val + align - 1) & !(align - 1)
+}
+
+/// Parse the `PT_GNU_PROPERTY` segment of a mapped library.
+pub(crate) unsafe fn cpu_features(base: usize, phdrs: &[Phdr]) -> CpuFeatures {
+    let mut features = CpuFeatures::empty();
+    let Some(phdr) = phdrs.iter().find(|phdr| phdr.p_type == PT_GNU_PROPERTY) else {
+        return features;
+    };
+    let read_u32 = |addr: usize| (addr as *const u32).read_unaligned();
+    let start = base + phdr.p_vaddr as usize;
+    let end = start + phdr.p_memsz as usize;
+    let mut note = start;
+    // 每个note由namesz、descsz、type、name和desc组成
+    while note + 12 <= end {
+        let namesz = read_u32(note) as usize;
+        let descsz = read_u32(note + 4) as usize;
+        let ty = read_u32(note + 8);
+        let desc = note + 12 + align_up(namesz, 4);
+        if ty == NT_GNU_PROPERTY_TYPE_0 {
+            let mut prop = desc;
+            while prop + 8 <= desc + descsz {
+                let pr_type = read_u32(prop);
+                let pr_datasz = read_u32(prop + 4) as usize;
+                if pr_datasz >= 4 {
+                    let data = read_u32(prop + 8);
+                    match pr_type {
+                        GNU_PROPERTY_X86_FEATURE_1_AND => {
+                            features |= CpuFeatures::from_bits_truncate(data & 0b11);
+                        }
+                        GNU_PROPERTY_AARCH64_FEATURE_1_AND => {
+                            features |= CpuFeatures::from_bits_truncate((data & 0b11) << 2);
+                        }
+                        _ => {}
+                    }
+                }
+                prop += 8 + align_up(pr_datasz, size_of::<usize>());
+            }
+        }
+        note = desc + align_up(descsz, phdr.p_align.max(4) as usize);
+    }
+    features
+}
@@ -0,0 +1,89 @@
+use super::deferred::{hide_tag, HiddenTags};
+use crate::Result;
+use alloc::format;
+use elf_loader::{abi::DT_NULL, arch::Dyn, ElfDylib};
+
+const DT_RELRSZ: i64 = 35;
+const DT_RELR: i64 = 36;
+
+/// The offset and the size of the relative relocations packed in `DT_RELR`.
+pub(crate) struct Relr {
+    off: usize,
+    size: usize,
+}
+
+/// Read `DT_RELR` and `DT_RELRSZ`, then hide `DT_RELR` so that the relocations are never applied twice.
+/// The relocations are applied by [`apply`] once all the segments are mapped.
+pub(crate) unsafe fn hide(dynamic: *mut Dyn, hidden: &mut HiddenTags) -> Option<Relr> {
+    let mut relr = None;
+    let mut relr_size = 0;
+    let mut cur = dynamic;
+    while (*cur).d_tag != DT_NULL {
+        match (*cur).d_tag {
+            DT_RELR => {
+                relr = Some((*cur).d_un as usize);
+                hide_tag(dynamic, cur, hidden);
+            }
+            DT_RELRSZ => relr_size = (*cur).d_un as usize,
+            _ => {}
+        }
+        cur = cur.add(1);
+    }
+    relr.map(|off| Relr {
+        off,
+        size: relr_size,
+    })
+}
+
+/// Apply the relative relocations packed in `DT_RELR`. Every entry and every address that is written
+/// must lie in the mapped segments of the library, otherwise an error is returned.
+pub(crate) unsafe fn apply(lib: &ElfDylib, relr: &Relr) -> Result<()> {
+    let base = lib.base();
+    let len = lib.map_len();
+    let word = size_of::<usize>();
+    // 重定位项来自库本身，不能信任
+    let in_bounds = |off: usize, size: usize| off.checked_add(size).is_some_and(|end| end <= len);
+    // 与elf_loader中越界的重定位项一样，返回重定位错误
+    let out_of_bounds = |off: usize| {
+        crate::Error::from(elf_loader::Error::RelocateError {
+            msg: format!(
+                "[{}] has a DT_RELR relocation at offset [0x{:x}], which is out of bounds",
+                lib.name(),
+                off
+            ),
+        })
+    };
+    if !in_bounds(relr.off, relr.size) {
+        return Err(out_of_bounds(relr.off));
+    }
+    let entries = core::slice::from_raw_parts((base + relr.off) as *const usize, relr.size / word);
+    log::trace!("Apply {} DT_RELR entries", entries.len());
+    let bits = usize::BITS as usize - 1;
+    // 下一个需要重定位的位置相对于base的偏移
+    let mut off = 0;
+    for &entry in entries {
+        // 偶数项是下一个需要重定位的地址，奇数项是从off开始的位图
+        if entry & 1 == 0 {
+            if !in_bounds(entry, word) {
+                return Err(out_of_bounds(entry));
+            }
+            *((base + entry) as *mut usize) += base;
+            off = entry + word;
+        } else {
+            let mut bitmap = entry >> 1;
+            let mut cur = off;
+            while bitmap != 0 {
+                if bitmap & 1 != 0 {
+                    if !in_bounds(cur, word) {
+                        return Err(out_of_bounds(cur));
+                    }
+                    *((base + cur) as *mut usize) += base;
+                }
+                bitmap >>= 1;
+                cur = cur.wrapping_add(word);
+            }
+            off = off.wrapping_add(bits * word);
+        }
+    }
+    Ok(())
+}
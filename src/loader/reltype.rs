@@ -0,0 +1,17 @@
+// elf_loader::arch中没有定义的重定位类型
+
+cfg_if::cfg_if! {
+    if #[cfg(target_arch = "x86_64")] {
+        use elf_loader::abi::{R_X86_64_COPY, R_X86_64_TPOFF64};
+        pub(crate) const REL_COPY: u32 = R_X86_64_COPY;
+        pub(crate) const REL_TPOFF: u32 = R_X86_64_TPOFF64;
+    } else if #[cfg(target_arch = "aarch64")] {
+        use elf_loader::abi::{R_AARCH64_COPY, R_AARCH64_TLS_TPREL};
+        pub(crate) const REL_COPY: u32 = R_AARCH64_COPY;
+        pub(crate) const REL_TPOFF: u32 = R_AARCH64_TLS_TPREL;
+    } else if #[cfg(target_arch = "riscv64")] {
+        use elf_loader::abi::{R_RISCV_COPY, R_RISCV_TLS_TPREL64};
+        pub(crate) const REL_COPY: u32 = R_RISCV_COPY;
+        pub(crate) const REL_TPOFF: u32 = R_RISCV_TLS_TPREL64;
+    }
+}
@@ -0,0 +1,48 @@
+use super::builtin;
+use crate::{Dylib, ElfLibrary, Result};
+use alloc::sync::Arc;
+
+/// A source of symbols outside dlopen-rs, such as libraries loaded by `libloading` or the system dynamic linker.
+/// It is used by [`ElfLibrary::relocate_with_extern`].
+pub trait ExternResolver {
+    /// Get the address of the symbol `name`, or `None` if it can not be found.
+    fn get_sym(&self, name: &str) -> Option<*const ()>;
+}
+
+impl ElfLibrary {
+    /// Use libraries and external resolvers to relocate the current library.
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use ::dlopen_rs::{ElfLibrary, ExternResolver, OpenFlags};
+    /// # use std::sync::Arc;
+    /// struct SystemLib(libloading::Library);
+    ///
+    /// impl ExternResolver for SystemLib {
+    ///     fn get_sym(&self, name: &str) -> Option<*const ()> {
+    ///         unsafe { self.0.get::<*const ()>(name.as_bytes()).ok().map(|sym| *sym) }
+    ///     }
+    /// }
+    ///
+    /// let libc = SystemLib(unsafe { libloading::Library::new("libc.so.6").unwrap() });
+    /// let lib = ElfLibrary::from_file("/path/to/awesome.module", OpenFlags::RTLD_LOCAL)
+    ///     .unwrap()
+    ///     .relocate_with_extern(&[], &[Arc::new(libc)])
+    ///     .unwrap();
+    /// ```
+    /// # Note
+    /// It will use the external resolvers in order to relocate current lib firstly.
+    pub fn relocate_with_extern<'a>(
+        self,
+        libs: impl AsRef<[Dylib<'a>]>,
+        externs: &[Arc<dyn ExternResolver>],
+    ) -> Result<Dylib<'a>> {
+        let find = |name: &str| {
+            externs
+                .iter()
+                .find_map(|resolver| resolver.get_sym(name))
+                .or(builtin::find(name))
+        };
+        self.relocate_impl(libs.as_ref(), &find)
+    }
+}
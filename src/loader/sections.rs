@@ -0,0 +1,88 @@
+use super::dynflags::read_table;
+use crate::Result;
+use alloc::{string::String, vec::Vec};
+use core::ffi::CStr;
+use elf_loader::object::ElfObject;
+
+const SHF_ALLOC: u64 = 0x2;
+// ELF64头部中e_shoff、e_shnum和e_shstrndx的偏移
+const E_SHOFF: usize = 0x28;
+const E_SHNUM: usize = 0x3c;
+const E_SHSTRNDX: usize = 0x3e;
+const EHDR_SIZE: usize = 0x40;
+
+// Elf64_Shdr
+#[repr(C)]
+#[allow(unused)]
+struct Shdr {
+    sh_name: u32,
+    sh_type: u32,
+    sh_flags: u64,
+    sh_addr: u64,
+    sh_offset: u64,
+    sh_size: u64,
+    sh_link: u32,
+    sh_info: u32,
+    sh_addralign: u64,
+    sh_entsize: u64,
+}
+
+/// A section of a dynamic library, see `Dylib::sections`.
+#[derive(Clone, Debug)]
+pub struct Section {
+    /// The name of the section, such as `.text`.
+    pub name: String,
+    /// The offset of the section from the base address of the library,
+    /// or `None` if the section is not mapped into memory (e.g. `.comment`).
+    pub offset: Option<usize>,
+    /// The size of the section in bytes.
+    pub size: usize,
+}
+
+fn read_sections(object: &mut impl ElfObject, size: usize) -> Result<Vec<Section>> {
+    let mut ehdr = [0u8; EHDR_SIZE];
+    object.read(&mut ehdr, 0)?;
+    let shoff = u64::from_ne_bytes(ehdr[E_SHOFF..E_SHOFF + 8].try_into().unwrap()) as usize;
+    let shnum = u16::from_ne_bytes(ehdr[E_SHNUM..E_SHNUM + 2].try_into().unwrap()) as usize;
+    let shstrndx =
+        u16::from_ne_bytes(ehdr[E_SHSTRNDX..E_SHSTRNDX + 2].try_into().unwrap()) as usize;
+    if shoff == 0 || shstrndx >= shnum {
+        return Ok(Vec::new());
+    }
+    let shdrs: Vec<Shdr> = read_table(object, size, shoff, shnum)?;
+    let strtab = &shdrs[shstrndx];
+    let mut names: Vec<u8> = read_table(
+        object,
+        size,
+        strtab.sh_offset as usize,
+        strtab.sh_size as usize,
+    )?;
+    // 保证最后一个名字以0结尾
+    names.push(0);
+    Ok(shdrs
+        .iter()
+        .skip(1)
+        .map(|shdr| Section {
+            name: names
+                .get(shdr.sh_name as usize..)
+                .and_then(|name| CStr::from_bytes_until_nul(name).ok())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            offset: (shdr.sh_flags & SHF_ALLOC != 0).then_some(shdr.sh_addr as usize),
+            size: shdr.sh_size as usize,
+        })
+        .collect())
+}
+
+/// Read the section header table of an unmapped elf object whose size is `size`. The sections are only used for introspection,
+/// so an empty list is returned if they can not be read.
+pub(crate) fn read(object: &mut impl ElfObject, size: usize) -> Vec<Section> {
+    read_sections(object, size).unwrap_or_else(|err| {
+        log::debug!(
+            "Failed to read the section headers of [{}]: {}",
+            object.file_name().to_string_lossy(),
+            err
+        );
+        Vec::new()
+    })
+}
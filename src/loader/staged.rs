@@ -0,0 +1,35 @@
+use alloc::{
+    boxed::Box,
+    string::{String, ToString},
+};
+use indexmap::IndexMap;
+use spin::{Lazy, RwLock};
+
+/// 用户预先放入内存的动态库，在no_std环境下用来查找依赖库
+pub(crate) static STAGED_BINARY: Lazy<RwLock<IndexMap<String, Box<[u8]>>>> =
+    Lazy::new(|| RwLock::new(IndexMap::new()));
+
+/// Stage the bytes of an elf dynamic library under `name`. When `dlopen_from_binary` can not find a
+/// dependency named `name` among the libraries already loaded, it loads the staged bytes instead.
+/// It is only available without the `std` feature, where there is no filesystem to search.
+/// Staging a library with an existing name replaces the old one.
+///
+/// # Examples
+/// ```no_run
+/// dlopen_rs::register_binary("libdep.so", include_bytes!("libdep.so").as_slice());
+/// let lib = dlopen_rs::ElfLibrary::dlopen_from_binary(
+///     include_bytes!("libplugin.so"),
+///     "libplugin.so",
+///     dlopen_rs::OpenFlags::RTLD_LOCAL,
+/// )
+/// .unwrap();
+/// ```
+pub fn register_binary(name: &str, bytes: impl Into<Box<[u8]>>) {
+    log::debug!("Stage the dynamic library [{}]", name);
+    STAGED_BINARY.write().insert(name.to_string(), bytes.into());
+}
+
+/// Remove a dynamic library staged by [`register_binary`].
+pub fn unregister_binary(name: &str) -> bool {
+    STAGED_BINARY.write().shift_remove(name).is_some()
+}
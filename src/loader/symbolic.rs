@@ -0,0 +1,48 @@
+use super::histogram;
+use core::ptr::NonNull;
+use elf_loader::{
+    abi::PT_GNU_RELRO,
+    arch::REL_SYMBOLIC,
+    mmap::{Mmap, MmapImpl, ProtFlags},
+    segment::PAGE_SIZE,
+    CoreComponent,
+};
+
+/// Add the addends of the `REL_SYMBOLIC` relocations (`S + A`) of a relocated library.
+///
+/// elf_loader writes only the address of the symbol, so a pointer into a data symbol such as `&array[1]` would point
+/// to the start of the symbol. The `PT_GNU_RELRO` segment has already been made read-only by elf_loader,
+/// so it is made writable while the addends are added.
+pub(crate) unsafe fn add_addends(core: &CoreComponent) {
+    let base = core.base();
+    let phdrs = super::phdrs(core);
+    let mut relas = histogram::relocations(base, phdrs)
+        .filter(|rela| rela.r_type() as u32 == REL_SYMBOLIC && rela.r_addend() != 0)
+        .peekable();
+    if relas.peek().is_none() {
+        return;
+    }
+    let relro = phdrs
+        .iter()
+        .find(|phdr| phdr.p_type == PT_GNU_RELRO)
+        .map(|phdr| {
+            // 与elf_loader设置relro时使用相同的页大小
+            let start = (base + phdr.p_vaddr as usize) & !(PAGE_SIZE - 1);
+            let end = (base + phdr.p_vaddr as usize + phdr.p_memsz as usize + PAGE_SIZE - 1)
+                & !(PAGE_SIZE - 1);
+            (NonNull::new_unchecked(start as _), end - start)
+        });
+    let protect = |prot: ProtFlags| {
+        if let Some((start, len)) = relro {
+            if MmapImpl::mprotect(start, len, prot).is_err() {
+                log::error!("mprotect the relro segment of [{}] failed", core.name());
+            }
+        }
+    };
+    protect(ProtFlags::PROT_READ | ProtFlags::PROT_WRITE);
+    for rela in relas {
+        let ptr = (base + rela.r_offset()) as *mut usize;
+        ptr.write(ptr.read().wrapping_add(rela.r_addend()));
+    }
+    protect(ProtFlags::PROT_READ);
+}
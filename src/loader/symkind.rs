@@ -0,0 +1,542 @@
+use crate::{find_symbol_error, Result};
+use alloc::{string::String, vec::Vec};
+use core::{borrow::Borrow, ffi::CStr};
+use elf_loader::{
+    abi::{DT_GNU_HASH, DT_HASH, DT_NULL, DT_STRTAB, DT_SYMTAB, PT_DYNAMIC, PT_LOAD, SHN_UNDEF},
+    arch::{Dyn, Phdr},
+    CoreComponent,
+};
+
+const STT_NOTYPE: u8 = 0;
+const STT_OBJECT: u8 = 1;
+const STT_FUNC: u8 = 2;
+const STT_TLS: u8 = 6;
+const STT_GNU_IFUNC: u8 = 10;
+const STB_WEAK: u8 = 2;
+
+/// The type of a symbol, taken from the `st_info` of its `.dynsym` entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymKind {
+    /// `STT_FUNC`: a function.
+    Func,
+    /// `STT_GNU_IFUNC`: a function whose address was chosen by a resolver.
+    IFunc,
+    /// `STT_OBJECT`: a data object, such as a static variable.
+    Object,
+    /// `STT_TLS`: a thread local variable. Its value is an offset in the TLS block, not an address.
+    Tls,
+    /// `STT_NOTYPE`: the type is not specified.
+    NoType,
+    /// Any other type.
+    Other(u8),
+}
+
+impl SymKind {
+    #[inline]
+    fn from_st_info(st_info: u8) -> SymKind {
+        match st_info & 0xf {
+            STT_FUNC => SymKind::Func,
+            STT_GNU_IFUNC => SymKind::IFunc,
+            STT_OBJECT => SymKind::Object,
+            STT_TLS => SymKind::Tls,
+            STT_NOTYPE => SymKind::NoType,
+            ty => SymKind::Other(ty),
+        }
+    }
+}
+
+// Elf64_Sym
+#[repr(C)]
+pub(crate) struct ElfSym {
+    st_name: u32,
+    st_info: u8,
+    st_other: u8,
+    st_shndx: u16,
+    st_value: u64,
+    st_size: u64,
+}
+
+fn gnu_hash(name: &[u8]) -> u32 {
+    name.iter()
+        .fold(5381u32, |h, c| h.wrapping_mul(33).wrapping_add(*c as u32))
+}
+
+fn elf_hash(name: &[u8]) -> u32 {
+    name.iter().fold(0u32, |h, c| {
+        let h = (h << 4).wrapping_add(*c as u32);
+        (h ^ ((h & 0xf000_0000) >> 24)) & 0x0fff_ffff
+    })
+}
+
+const DT_VERSYM: i64 = 0x6ffffff0;
+const DT_VERDEF: i64 = 0x6ffffffc;
+const DT_VERDEFNUM: i64 = 0x6ffffffd;
+const DT_VERNEED: i64 = 0x6ffffffe;
+const DT_VERNEEDNUM: i64 = 0x6fffffff;
+
+pub(crate) struct DynTables {
+    pub(crate) symtab: usize,
+    pub(crate) strtab: usize,
+    gnu_hashtab: Option<usize>,
+    hashtab: Option<usize>,
+    #[allow(unused)]
+    pub(crate) versym: Option<usize>,
+    #[allow(unused)]
+    pub(crate) verdef: Option<(usize, usize)>,
+    #[allow(unused)]
+    pub(crate) verneed: Option<(usize, usize)>,
+    // 库的PT_LOAD段所占的地址范围
+    start: usize,
+    end: usize,
+}
+
+impl DynTables {
+    /// Whether `[ptr, ptr + len)` lies in the mapped segments of the library.
+    #[inline]
+    fn contains(&self, ptr: usize, len: usize) -> bool {
+        ptr >= self.start && ptr.checked_add(len).is_some_and(|end| end <= self.end)
+    }
+
+    /// Get the entry `idx` of the `.dynsym`, or `None` if it lies outside the mapped segments.
+    #[inline]
+    unsafe fn sym(&self, idx: usize) -> Option<&ElfSym> {
+        let ptr = idx
+            .checked_mul(size_of::<ElfSym>())?
+            .checked_add(self.symtab)?;
+        self.contains(ptr, size_of::<ElfSym>())
+            .then(|| &*(ptr as *const ElfSym))
+    }
+
+    /// Get the word `idx` of the `u32` array at `table`, or `None` if it lies outside the mapped segments.
+    #[inline]
+    unsafe fn word(&self, table: usize, idx: usize) -> Option<u32> {
+        let ptr = idx.checked_mul(size_of::<u32>())?.checked_add(table)?;
+        self.contains(ptr, size_of::<u32>())
+            .then(|| *(ptr as *const u32))
+    }
+}
+
+pub(crate) unsafe fn dyn_tables(base: usize, phdrs: &[Phdr]) -> Option<DynTables> {
+    let dynamic = phdrs.iter().find(|phdr| phdr.p_type == PT_DYNAMIC)?;
+    let (start, end) = phdrs.iter().filter(|phdr| phdr.p_type == PT_LOAD).fold(
+        (usize::MAX, 0),
+        |(start, end), phdr| {
+            let vaddr = phdr.p_vaddr as usize;
+            (
+                start.min(vaddr),
+                end.max(vaddr.saturating_add(phdr.p_memsz as usize)),
+            )
+        },
+    );
+    if start >= end {
+        return None;
+    }
+    // 系统动态链接器加载的库，其动态段中的地址已经被修正为绝对地址
+    let addr = |ptr: usize| if ptr >= base { ptr } else { base + ptr };
+    let mut tables = DynTables {
+        symtab: 0,
+        strtab: 0,
+        gnu_hashtab: None,
+        hashtab: None,
+        versym: None,
+        verdef: None,
+        verneed: None,
+        start: base.checked_add(start)?,
+        end: base.checked_add(end)?,
+    };
+    let mut verdef = None;
+    let mut verdef_num = 0;
+    let mut verneed = None;
+    let mut verneed_num = 0;
+    let dynamic_addr = base.checked_add(dynamic.p_vaddr as usize)?;
+    if !tables.contains(dynamic_addr, dynamic.p_memsz as usize) {
+        return None;
+    }
+    // 动态段可能缺少DT_NULL，所以最多只读取动态段中的项
+    let dynamic = core::slice::from_raw_parts(
+        dynamic_addr as *const Dyn,
+        dynamic.p_memsz as usize / size_of::<Dyn>(),
+    );
+    for entry in dynamic.iter().take_while(|entry| entry.d_tag != DT_NULL) {
+        match entry.d_tag {
+            DT_SYMTAB => tables.symtab = addr(entry.d_un as usize),
+            DT_STRTAB => tables.strtab = addr(entry.d_un as usize),
+            DT_GNU_HASH => tables.gnu_hashtab = Some(addr(entry.d_un as usize)),
+            DT_HASH => tables.hashtab = Some(addr(entry.d_un as usize)),
+            DT_VERSYM => tables.versym = Some(addr(entry.d_un as usize)),
+            DT_VERDEF => verdef = Some(addr(entry.d_un as usize)),
+            DT_VERDEFNUM => verdef_num = entry.d_un as usize,
+            DT_VERNEED => verneed = Some(addr(entry.d_un as usize)),
+            DT_VERNEEDNUM => verneed_num = entry.d_un as usize,
+            _ => {}
+        }
+    }
+    if !tables.contains(tables.symtab, size_of::<ElfSym>()) || !tables.contains(tables.strtab, 1) {
+        return None;
+    }
+    tables.verdef = verdef.map(|verdef| (verdef, verdef_num));
+    tables.verneed = verneed.map(|verneed| (verneed, verneed_num));
+    Some(tables)
+}
+
+// DT_GNU_HASH的头部，其中的bucket数组已经检查过位于库映射的段中
+struct GnuHash {
+    nbucket: usize,
+    symoffset: usize,
+    buckets: usize,
+    chain: usize,
+}
+
+unsafe fn gnu_hash_table(tables: &DynTables, table: usize) -> Option<GnuHash> {
+    let nbucket = tables.word(table, 0)? as usize;
+    let symoffset = tables.word(table, 1)? as usize;
+    let bloom_size = tables.word(table, 2)? as usize;
+    let buckets = bloom_size
+        .checked_mul(size_of::<usize>())?
+        .checked_add(table.checked_add(16)?)?;
+    let chain = nbucket
+        .checked_mul(size_of::<u32>())?
+        .checked_add(buckets)?;
+    if !tables.contains(buckets, chain - buckets) {
+        return None;
+    }
+    // 索引小于symoffset的符号不在哈希表中，但仍然位于符号表中
+    if symoffset != 0 {
+        tables.sym(symoffset - 1)?;
+    }
+    Some(GnuHash {
+        nbucket,
+        symoffset,
+        buckets,
+        chain,
+    })
+}
+
+// DT_HASH的头部，其中的bucket和chain数组已经检查过位于库映射的段中
+struct ElfHash {
+    nbucket: usize,
+    nchain: usize,
+    buckets: usize,
+    chain: usize,
+}
+
+unsafe fn elf_hash_table(tables: &DynTables, table: usize) -> Option<ElfHash> {
+    let nbucket = tables.word(table, 0)? as usize;
+    let nchain = tables.word(table, 1)? as usize;
+    let buckets = table.checked_add(8)?;
+    let chain = nbucket
+        .checked_mul(size_of::<u32>())?
+        .checked_add(buckets)?;
+    tables.word(chain, nchain.checked_sub(1)?)?;
+    // nchain就是符号表中符号的数量
+    tables.sym(nchain - 1)?;
+    Some(ElfHash {
+        nbucket,
+        nchain,
+        buckets,
+        chain,
+    })
+}
+
+/// Get the number of entries in the `.dynsym` of a mapped library. `DT_HASH` records it as `nchain`,
+/// while for `DT_GNU_HASH` it is found by walking the chain of the last non-empty bucket.
+/// It returns `Ok(None)` if the library has no hash table, and an error if a hash table does not lie in
+/// the mapped segments or the `DT_GNU_HASH` has no buckets.
+pub(crate) unsafe fn symbol_count(base: usize, phdrs: &[Phdr]) -> Result<Option<usize>> {
+    let Some(tables) = dyn_tables(base, phdrs) else {
+        return Ok(None);
+    };
+    let malformed = || find_symbol_error("the hash table of the dynamic symbols is malformed");
+    // 没有bucket时查找符号会除以0
+    let gnu_hash = tables
+        .gnu_hashtab
+        .map(|table| {
+            gnu_hash_table(&tables, table)
+                .filter(|hash| hash.nbucket != 0)
+                .ok_or_else(malformed)
+        })
+        .transpose()?;
+    if let Some(table) = tables.hashtab {
+        let hash = elf_hash_table(&tables, table).ok_or_else(malformed)?;
+        return Ok(Some(hash.nchain));
+    }
+    let Some(hash) = gnu_hash else {
+        return Ok(None);
+    };
+    let mut last = 0;
+    for idx in 0..hash.nbucket {
+        last = last.max(tables.word(hash.buckets, idx).ok_or_else(malformed)? as usize);
+    }
+    if last < hash.symoffset {
+        return Ok(Some(hash.symoffset));
+    }
+    // 链的末尾由最低位为1的哈希值标记，没有标记的链会在离开映射的段时返回错误
+    let mut idx = last;
+    loop {
+        let chain_hash = tables
+            .word(hash.chain, idx - hash.symoffset)
+            .ok_or_else(malformed)?;
+        tables.sym(idx).ok_or_else(malformed)?;
+        if chain_hash & 1 != 0 {
+            return Ok(Some(idx + 1));
+        }
+        idx += 1;
+    }
+}
+
+/// Call `f` with the index and the entry of each defined symbol named `name` in the `.dynsym` of a mapped library,
+/// found through `DT_GNU_HASH` or `DT_HASH`, until `f` returns `true`. Entries outside the mapped segments end the walk.
+pub(crate) unsafe fn for_each_defined(
+    tables: &DynTables,
+    name: &[u8],
+    mut f: impl FnMut(usize, &ElfSym) -> bool,
+) {
+    // 返回Some表示查找结束
+    let mut check = |idx: usize| {
+        let Some(sym) = tables.sym(idx) else {
+            return Some(());
+        };
+        let name_ptr = tables.strtab.checked_add(sym.st_name as usize)?;
+        if sym.st_shndx == SHN_UNDEF || !tables.contains(name_ptr, 1) {
+            return None;
+        }
+        let sym_name = CStr::from_ptr(name_ptr as _);
+        (sym_name.to_bytes() == name && f(idx, sym)).then_some(())
+    };
+    if let Some(table) = tables.gnu_hashtab {
+        let Some(hash_table) = gnu_hash_table(tables, table) else {
+            return;
+        };
+        if hash_table.nbucket == 0 {
+            return;
+        }
+        let hash = gnu_hash(name);
+        let Some(idx) = tables.word(hash_table.buckets, hash as usize % hash_table.nbucket) else {
+            return;
+        };
+        let mut idx = idx as usize;
+        if idx < hash_table.symoffset {
+            return;
+        }
+        // 链中的索引是递增的，离开映射的段时结束
+        while let Some(chain_hash) = tables.word(hash_table.chain, idx - hash_table.symoffset) {
+            if chain_hash | 1 == hash | 1 && check(idx).is_some() {
+                return;
+            }
+            if chain_hash & 1 != 0 {
+                return;
+            }
+            idx += 1;
+        }
+        return;
+    }
+    let Some(table) = tables.hashtab else {
+        return;
+    };
+    let Some(hash_table) = elf_hash_table(tables, table) else {
+        return;
+    };
+    if hash_table.nbucket == 0 {
+        return;
+    }
+    let Some(idx) = tables.word(
+        hash_table.buckets,
+        elf_hash(name) as usize % hash_table.nbucket,
+    ) else {
+        return;
+    };
+    let mut idx = idx as usize;
+    // 链中可能有环，所以最多走nchain步
+    for _ in 0..hash_table.nchain {
+        if idx == 0 || idx >= hash_table.nchain || check(idx).is_some() {
+            return;
+        }
+        idx = *((hash_table.chain + idx * size_of::<u32>()) as *const u32) as usize;
+    }
+}
+
+/// Find the defined symbol `name` in the `.dynsym` of a mapped library.
+/// It returns the type and the `st_size` of the symbol.
+pub(crate) unsafe fn lookup(base: usize, phdrs: &[Phdr], name: &str) -> Option<(SymKind, usize)> {
+    let tables = dyn_tables(base, phdrs)?;
+    let mut res = None;
+    for_each_defined(&tables, name.as_bytes(), |_, sym| {
+        res = Some((SymKind::from_st_info(sym.st_info), sym.st_size as usize));
+        true
+    });
+    res
+}
+
+/// Find the defined symbol `name` and return its `st_value`, which is relative to the base address of the library.
+pub(crate) unsafe fn offset(base: usize, phdrs: &[Phdr], name: &str) -> Option<u64> {
+    let tables = dyn_tables(base, phdrs)?;
+    let mut res = None;
+    for_each_defined(&tables, name.as_bytes(), |_, sym| {
+        res = Some(sym.st_value);
+        true
+    });
+    res
+}
+
+/// Find the defined symbol `name`, which may not be valid UTF-8, and return its address.
+/// Thread local symbols are skipped, since their values are not addresses.
+pub(crate) unsafe fn address(base: usize, phdrs: &[Phdr], name: &[u8]) -> Option<usize> {
+    let tables = dyn_tables(base, phdrs)?;
+    let mut res = None;
+    for_each_defined(&tables, name, |_, sym| {
+        res = (sym.st_info & 0xf != STT_TLS).then_some(base + sym.st_value as usize);
+        res.is_some()
+    });
+    res
+}
+
+/// The libraries of a relocation scope with their dynamic tables, which are read once per load.
+/// It is used to prefer a `STB_GLOBAL` definition over the `STB_WEAK` ones before it, see [`StrongScope::find`].
+pub(crate) struct StrongScope {
+    libs: Vec<(String, usize, DynTables)>,
+}
+
+impl StrongScope {
+    pub(crate) fn new(libs: impl Iterator<Item = impl Borrow<CoreComponent>>) -> StrongScope {
+        let libs = libs
+            .filter_map(|lib| {
+                let lib = lib.borrow();
+                let tables = unsafe { dyn_tables(lib.base(), super::phdrs(lib)) }?;
+                Some((String::from(lib.shortname()), lib.base(), tables))
+            })
+            .collect();
+        StrongScope { libs }
+    }
+
+    /// Find `name` in the libraries in order like elf_loader does during relocation, except that a `STB_GLOBAL` definition
+    /// is preferred over the `STB_WEAK` ones before it. Definitions rejected by `accept`, such as the ones with another
+    /// symbol version, are skipped. It returns `None` if the first definition is not weak, so that elf_loader finds it as usual.
+    pub(crate) fn find(
+        &self,
+        name: &str,
+        accept: impl Fn(&DynTables, usize) -> bool,
+    ) -> Option<*const ()> {
+        let mut weak = false;
+        for (shortname, base, tables) in &self.libs {
+            let mut def = None;
+            unsafe {
+                for_each_defined(tables, name.as_bytes(), |idx, sym| {
+                    if !accept(tables, idx) {
+                        return false;
+                    }
+                    def = Some((sym.st_info, base + sym.st_value as usize));
+                    true
+                })
+            };
+            let Some((st_info, addr)) = def else {
+                continue;
+            };
+            // 线程局部变量的值不是地址，交给elf_loader处理
+            if st_info & 0xf == STT_TLS {
+                return None;
+            }
+            if st_info >> 4 == STB_WEAK {
+                weak = true;
+                continue;
+            }
+            if !weak {
+                return None;
+            }
+            log::debug!(
+                "Use the strong definition of [{}] in [{}] instead of a weak one",
+                name,
+                shortname
+            );
+            if st_info & 0xf == STT_GNU_IFUNC {
+                let ifunc: extern "C" fn() -> usize = unsafe { core::mem::transmute(addr) };
+                return Some(ifunc() as _);
+            }
+            return Some(addr as _);
+        }
+        None
+    }
+}
+
+/// Find the thread local symbol `name` and return its offset in the TLS block of the library.
+#[cfg(feature = "tls")]
+pub(crate) unsafe fn tls_offset(base: usize, phdrs: &[Phdr], name: &str) -> Option<usize> {
+    let tables = dyn_tables(base, phdrs)?;
+    let mut res = None;
+    for_each_defined(&tables, name.as_bytes(), |_, sym| {
+        res = (sym.st_info & 0xf == STT_TLS).then_some(sym.st_value as usize);
+        true
+    });
+    res
+}
+
+/// Find the defined symbol that is the closest one at or below `addr` in the `.dynsym` of a mapped library.
+/// It returns the name, the address and the entry of the symbol.
+pub(crate) unsafe fn nearest<'a>(
+    base: usize,
+    phdrs: &[Phdr],
+    addr: usize,
+) -> Option<(&'a CStr, usize, *const ElfSym)> {
+    let count = symbol_count(base, phdrs).ok().flatten()?;
+    let tables = dyn_tables(base, phdrs)?;
+    let mut res: Option<(&CStr, usize, *const ElfSym)> = None;
+    for idx in 1..count {
+        let sym = (tables.symtab as *const ElfSym).add(idx);
+        let entry = &*sym;
+        let ty = entry.st_info & 0xf;
+        if entry.st_shndx == SHN_UNDEF || !matches!(ty, STT_FUNC | STT_OBJECT | STT_GNU_IFUNC) {
+            continue;
+        }
+        let start = base + entry.st_value as usize;
+        if start > addr || (entry.st_size != 0 && addr >= start + entry.st_size as usize) {
+            continue;
+        }
+        if res.is_none_or(|(_, cur, _)| start > cur) {
+            let name = CStr::from_ptr((tables.strtab + entry.st_name as usize) as _);
+            res = Some((name, start, sym));
+        }
+    }
+    res
+}
+
+/// Call `f` with the name, the address and the `st_size` of each function defined in the `.dynsym` of a mapped library.
+#[cfg(feature = "std")]
+pub(crate) unsafe fn for_each_func(
+    base: usize,
+    phdrs: &[Phdr],
+    mut f: impl FnMut(&CStr, usize, usize),
+) {
+    let (Ok(Some(count)), Some(tables)) = (symbol_count(base, phdrs), dyn_tables(base, phdrs))
+    else {
+        return;
+    };
+    for idx in 1..count {
+        let entry = &*(tables.symtab as *const ElfSym).add(idx);
+        if entry.st_shndx == SHN_UNDEF || !matches!(entry.st_info & 0xf, STT_FUNC | STT_GNU_IFUNC) {
+            continue;
+        }
+        let name = CStr::from_ptr((tables.strtab + entry.st_name as usize) as _);
+        f(name, base + entry.st_value as usize, entry.st_size as usize);
+    }
+}
+
+/// Call `f` with the index and the name of each undefined symbol in the `.dynsym` of a mapped library.
+#[cfg(feature = "version")]
+pub(crate) unsafe fn for_each_undefined<'a>(
+    base: usize,
+    phdrs: &[Phdr],
+    mut f: impl FnMut(usize, &'a CStr),
+) {
+    let (Ok(Some(count)), Some(tables)) = (symbol_count(base, phdrs), dyn_tables(base, phdrs))
+    else {
+        return;
+    };
+    for idx in 1..count {
+        let entry = &*(tables.symtab as *const ElfSym).add(idx);
+        if entry.st_shndx == SHN_UNDEF {
+            f(
+                idx,
+                CStr::from_ptr((tables.strtab + entry.st_name as usize) as _),
+            );
+        }
+    }
+}
@@ -0,0 +1,315 @@
+use super::symkind;
+use crate::{find_symbol_error, register::registry_name, Dylib, Result};
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::ffi::CStr;
+use elf_loader::{CoreComponent, Symbol};
+
+const VERSYM_HIDDEN: u16 = 0x8000;
+const VER_FLG_BASE: u16 = 0x1;
+
+// Elf64_Verdef
+#[allow(unused)]
+#[repr(C)]
+struct Verdef {
+    vd_version: u16,
+    vd_flags: u16,
+    vd_ndx: u16,
+    vd_cnt: u16,
+    vd_hash: u32,
+    vd_aux: u32,
+    vd_next: u32,
+}
+
+// Elf64_Verdaux
+#[allow(unused)]
+#[repr(C)]
+struct Verdaux {
+    vda_name: u32,
+    vda_next: u32,
+}
+
+// Elf64_Verneed
+#[allow(unused)]
+#[repr(C)]
+struct Verneed {
+    vn_version: u16,
+    vn_cnt: u16,
+    vn_file: u32,
+    vn_aux: u32,
+    vn_next: u32,
+}
+
+// Elf64_Vernaux
+#[allow(unused)]
+#[repr(C)]
+struct Vernaux {
+    vna_hash: u32,
+    vna_flags: u16,
+    vna_other: u16,
+    vna_name: u32,
+    vna_next: u32,
+}
+
+/// Whether the version `name` is defined in `DT_VERDEF`.
+unsafe fn defines_version(strtab: usize, verdef: (usize, usize), name: &CStr) -> bool {
+    let (mut cur, num) = verdef;
+    for _ in 0..num {
+        let def = &*(cur as *const Verdef);
+        let aux = &*((cur + def.vd_aux as usize) as *const Verdaux);
+        if def.vd_flags & VER_FLG_BASE == 0
+            && CStr::from_ptr((strtab + aux.vda_name as usize) as _) == name
+        {
+            return true;
+        }
+        if def.vd_next == 0 {
+            break;
+        }
+        cur += def.vd_next as usize;
+    }
+    false
+}
+
+/// Find the name of the version with the index `ndx` in `DT_VERDEF`.
+unsafe fn version_cstr<'a>(strtab: usize, verdef: (usize, usize), ndx: u16) -> Option<&'a CStr> {
+    let (mut cur, num) = verdef;
+    for _ in 0..num {
+        let def = &*(cur as *const Verdef);
+        if def.vd_ndx == ndx && def.vd_flags & VER_FLG_BASE == 0 {
+            let aux = &*((cur + def.vd_aux as usize) as *const Verdaux);
+            return Some(CStr::from_ptr((strtab + aux.vda_name as usize) as _));
+        }
+        if def.vd_next == 0 {
+            break;
+        }
+        cur += def.vd_next as usize;
+    }
+    None
+}
+
+unsafe fn version_name(strtab: usize, verdef: (usize, usize), ndx: u16) -> Option<String> {
+    version_cstr(strtab, verdef, ndx)?
+        .to_str()
+        .ok()
+        .map(String::from)
+}
+
+/// Get the name of the default version of the symbol `name` defined in `lib`, or `None` if it is not versioned.
+///
+/// elf_loader returns the first definition it finds when no version is given, which may be a hidden (non-default) one,
+/// so the symbol is looked up again with the name of the default version.
+pub(crate) unsafe fn default_version(lib: &CoreComponent, name: &str) -> Option<String> {
+    let tables = symkind::dyn_tables(lib.base(), super::phdrs(lib))?;
+    let (versym, verdef) = (tables.versym?, tables.verdef?);
+    let mut res = None;
+    symkind::for_each_defined(&tables, name.as_bytes(), |idx, _| {
+        let ver = *(versym as *const u16).add(idx);
+        if ver & VERSYM_HIDDEN != 0 {
+            return false;
+        }
+        res = version_name(tables.strtab, verdef, ver);
+        true
+    });
+    res
+}
+
+/// The versions required by the undefined symbols of a library (from `DT_VERNEED`), keyed by the name of the symbol,
+/// together with whether the required version is hidden.
+pub(crate) struct Requirements<'a> {
+    versions: BTreeMap<&'a [u8], (&'a CStr, bool)>,
+}
+
+impl Requirements<'_> {
+    pub(crate) unsafe fn new(lib: &CoreComponent) -> Self {
+        let mut versions = BTreeMap::new();
+        let Some(tables) = symkind::dyn_tables(lib.base(), super::phdrs(lib)) else {
+            return Requirements { versions };
+        };
+        let (Some(versym), Some((mut cur, num))) = (tables.versym, tables.verneed) else {
+            return Requirements { versions };
+        };
+        // 版本的索引和名字
+        let mut names = Vec::new();
+        for _ in 0..num {
+            let need = &*(cur as *const Verneed);
+            let mut aux_cur = cur + need.vn_aux as usize;
+            for _ in 0..need.vn_cnt {
+                let aux = &*(aux_cur as *const Vernaux);
+                names.push((
+                    aux.vna_other,
+                    CStr::from_ptr((tables.strtab + aux.vna_name as usize) as _),
+                ));
+                if aux.vna_next == 0 {
+                    break;
+                }
+                aux_cur += aux.vna_next as usize;
+            }
+            if need.vn_next == 0 {
+                break;
+            }
+            cur += need.vn_next as usize;
+        }
+        symkind::for_each_undefined(lib.base(), super::phdrs(lib), |idx, name| {
+            let ver = *(versym as *const u16).add(idx);
+            if let Some((_, version)) = names.iter().find(|(ndx, _)| *ndx == ver & !VERSYM_HIDDEN) {
+                versions.insert(name.to_bytes(), (*version, ver & VERSYM_HIDDEN != 0));
+            }
+        });
+        Requirements { versions }
+    }
+
+    /// Whether the definition `idx` in `tables` satisfies the version required for `name`, checked like elf_loader does:
+    /// either the versions are the same, or neither of them is hidden.
+    pub(crate) unsafe fn accept(
+        &self,
+        name: &str,
+        tables: &symkind::DynTables,
+        idx: usize,
+    ) -> bool {
+        let Some((version, hidden)) = self.versions.get(name.as_bytes()) else {
+            return true;
+        };
+        let (Some(versym), Some(verdef)) = (tables.versym, tables.verdef) else {
+            return true;
+        };
+        let ver = *(versym as *const u16).add(idx);
+        (!hidden && ver & VERSYM_HIDDEN == 0)
+            || version_cstr(tables.strtab, verdef, ver & !VERSYM_HIDDEN) == Some(*version)
+    }
+}
+
+impl Dylib<'_> {
+    /// Get the versions that the symbol `name` is defined with in the dynamic library itself (from `DT_VERDEF`),
+    /// and whether each one is the default version, which is the one used by [`Dylib::get`].
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let libc = ElfLibrary::load_existing("libc.so.6").unwrap();
+    /// for (version, is_default) in libc.symbol_versions("memcpy") {
+    ///     println!("memcpy@{}{}", if is_default { "@" } else { "" }, version);
+    /// }
+    /// ```
+    pub fn symbol_versions(&self, name: &str) -> Vec<(String, bool)> {
+        let mut versions = Vec::new();
+        unsafe {
+            let Some(tables) = symkind::dyn_tables(self.base(), self.phdrs()) else {
+                return versions;
+            };
+            let (Some(versym), Some(verdef)) = (tables.versym, tables.verdef) else {
+                return versions;
+            };
+            symkind::for_each_defined(&tables, name.as_bytes(), |idx, _| {
+                let ver = *(versym as *const u16).add(idx);
+                if let Some(version) = version_name(tables.strtab, verdef, ver & !VERSYM_HIDDEN) {
+                    versions.push((version, ver & VERSYM_HIDDEN == 0));
+                }
+                false
+            });
+        }
+        versions
+    }
+
+    /// Load the symbol `name` with its default version, and return the name of the version too.
+    /// The default version is the one marked with `@@` in the version script of the library.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let libc = ElfLibrary::load_existing("libc.so.6").unwrap();
+    /// let (memcpy, version) = unsafe { libc.get_version_default::<fn()>("memcpy").unwrap() };
+    /// ```
+    pub unsafe fn get_version_default<'lib, T>(
+        &'lib self,
+        name: &str,
+    ) -> Result<(Symbol<'lib, T>, String)> {
+        let version = self
+            .symbol_versions(name)
+            .into_iter()
+            .find_map(|(version, is_default)| is_default.then_some(version))
+            .ok_or(find_symbol_error(format!(
+                "can not find the default version of symbol:{}",
+                name
+            )))?;
+        let symbol = self.get_version(name, &version)?;
+        Ok((symbol, version))
+    }
+
+    /// Check that every version required by the dynamic library (from `DT_VERNEED`) is defined by the library in `libs`
+    /// that it is required from, e.g. `GLIBC_2.14` from `libc.so.6`. The libraries are matched by `DT_SONAME`, or by file name.
+    ///
+    /// If some requirements are not satisfied, the error lists each of them as `(file, version)`, including the ones whose
+    /// library is not in `libs`.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # use dlopen_rs::{ElfLibrary, OpenFlags};
+    /// let libc = ElfLibrary::load_existing("libc.so.6").unwrap();
+    /// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LOCAL).unwrap();
+    /// if let Err(missing) = lib.check_version_requirements(&[libc]) {
+    ///     for (file, version) in missing {
+    ///         eprintln!("missing symbol version {}@{}", version, file);
+    ///     }
+    /// }
+    /// ```
+    pub fn check_version_requirements(
+        &self,
+        libs: &[Dylib<'_>],
+    ) -> core::result::Result<(), Vec<(String, String)>> {
+        let mut missing = Vec::new();
+        unsafe {
+            let Some(tables) = symkind::dyn_tables(self.base(), self.phdrs()) else {
+                return Ok(());
+            };
+            let Some((mut cur, num)) = tables.verneed else {
+                return Ok(());
+            };
+            for _ in 0..num {
+                let need = &*(cur as *const Verneed);
+                let file = CStr::from_ptr((tables.strtab + need.vn_file as usize) as _);
+                let file = file.to_string_lossy();
+                // 提供该版本的库的动态表
+                let provider = libs
+                    .iter()
+                    .find(|lib| registry_name(&lib.inner) == file || lib.inner.shortname() == file)
+                    .and_then(|lib| symkind::dyn_tables(lib.base(), lib.phdrs()));
+                let mut aux_cur = cur + need.vn_aux as usize;
+                for _ in 0..need.vn_cnt {
+                    let aux = &*(aux_cur as *const Vernaux);
+                    let version = CStr::from_ptr((tables.strtab + aux.vna_name as usize) as _);
+                    let found = provider.as_ref().is_some_and(|provider| {
+                        provider
+                            .verdef
+                            .is_some_and(|verdef| defines_version(provider.strtab, verdef, version))
+                    });
+                    if !found {
+                        log::warn!(
+                            "[{}] requires version [{}] of [{}], which is not defined",
+                            self.inner.shortname(),
+                            version.to_string_lossy(),
+                            file
+                        );
+                        missing.push((file.to_string(), version.to_string_lossy().into_owned()));
+                    }
+                    if aux.vna_next == 0 {
+                        break;
+                    }
+                    aux_cur += aux.vna_next as usize;
+                }
+                if need.vn_next == 0 {
+                    break;
+                }
+                cur += need.vn_next as usize;
+            }
+        }
+        if missing.is_empty() {
+            Ok(())
+        } else {
+            Err(missing)
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use elf_loader::{
+    abi::{PF_R, PF_W, PF_X, PT_LOAD},
+    CoreComponent,
+};
+
+/// Makes the read-only `PT_LOAD` segments of a library with `DT_TEXTREL` writable while it is alive,
+/// and restores their protection when dropped.
+pub(crate) struct TextRelGuard {
+    core: CoreComponent,
+}
+
+#[inline]
+fn prot(p_flags: u32) -> libc::c_int {
+    let mut prot = libc::PROT_NONE;
+    if p_flags & PF_R != 0 {
+        prot |= libc::PROT_READ;
+    }
+    if p_flags & PF_W != 0 {
+        prot |= libc::PROT_WRITE;
+    }
+    if p_flags & PF_X != 0 {
+        prot |= libc::PROT_EXEC;
+    }
+    prot
+}
+
+impl TextRelGuard {
+    pub(crate) fn new(core: CoreComponent) -> TextRelGuard {
+        log::debug!(
+            "Making the text segments of [{}] writable for relocation",
+            core.name()
+        );
+        let guard = TextRelGuard { core };
+        guard.protect(true);
+        guard
+    }
+
+    fn protect(&self, writable: bool) {
+        let page_size = unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize;
+        let base = self.core.base();
+        for phdr in super::phdrs(&self.core)
+            .iter()
+            .filter(|phdr| phdr.p_type == PT_LOAD && phdr.p_flags & PF_W == 0)
+        {
+            let start = (base + phdr.p_vaddr as usize) & !(page_size - 1);
+            let end = (base + phdr.p_vaddr as usize + phdr.p_memsz as usize + page_size - 1)
+                & !(page_size - 1);
+            let mut prot = prot(phdr.p_flags);
+            if writable {
+                prot |= libc::PROT_WRITE;
+            }
+            if unsafe { libc::mprotect(start as _, end - start, prot) } != 0 {
+                log::error!(
+                    "mprotect the text segment of [{}] failed: {}",
+                    self.core.name(),
+                    std::io::Error::last_os_error()
+                );
+            }
+        }
+    }
+}
+
+impl Drop for TextRelGuard {
+    fn drop(&mut self) {
+        self.protect(false);
+    }
+}
@@ -18,6 +18,16 @@ mod imp {
         ti_offset: usize,
     }
 
+    impl TlsIndex {
+        /// Create the index of the variable at `offset` (`st_value`) in the TLS block of the module.
+        pub(crate) fn new(module_id: usize, offset: usize) -> Self {
+            Self {
+                ti_module: module_id,
+                ti_offset: offset.wrapping_sub(TLS_DTV_OFFSET),
+            }
+        }
+    }
+
     struct TlsInner {
         image: *const u8,
         len: usize,
@@ -65,6 +75,11 @@ mod imp {
         pub(crate) fn module_id(&self) -> usize {
             self.inner.as_ref() as *const TlsInner as usize
         }
+
+        /// The initialization image of the TLS block (`.tdata`).
+        pub(crate) fn template(&self) -> &[u8] {
+            unsafe { core::slice::from_raw_parts(self.inner.image, self.inner.len) }
+        }
     }
 
     impl Drop for ElfTls {
@@ -110,4 +125,4 @@ mod imp {
 
 pub(crate) use imp::tls_get_addr;
 #[cfg(feature = "tls")]
-pub(crate) use imp::ElfTls;
+pub(crate) use imp::{ElfTls, TlsIndex};
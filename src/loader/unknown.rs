@@ -0,0 +1,68 @@
+use alloc::sync::Arc;
+use elf_loader::{arch::ElfRela, ElfDylib};
+use spin::RwLock;
+
+/// A callback handling a relocation of an unsupported type, see [`UnknownRelocPolicy::Custom`].
+pub type UnknownRelocHandler = Arc<dyn Fn(&ElfRela, usize) -> bool + Send + Sync>;
+
+/// How to handle a relocation whose type dlopen-rs does not support, see [`set_unknown_reloc_policy`].
+#[derive(Clone, Default)]
+pub enum UnknownRelocPolicy {
+    /// Fail to relocate the library.
+    #[default]
+    Fail,
+    /// Leave the relocated slot untouched and continue. The offsets of the skipped relocations are logged.
+    Skip,
+    /// Let the callback handle the relocation. It receives the relocation entry and the base address of the library,
+    /// and returns whether the relocation was handled. The relocation fails if it returns `false`.
+    Custom(UnknownRelocHandler),
+}
+
+impl core::fmt::Debug for UnknownRelocPolicy {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            UnknownRelocPolicy::Fail => f.write_str("Fail"),
+            UnknownRelocPolicy::Skip => f.write_str("Skip"),
+            UnknownRelocPolicy::Custom(_) => f.write_str("Custom"),
+        }
+    }
+}
+
+static POLICY: RwLock<UnknownRelocPolicy> = RwLock::new(UnknownRelocPolicy::Fail);
+
+/// Set how relocations of unsupported types are handled for the libraries relocated afterwards.
+/// The default is [`UnknownRelocPolicy::Fail`].
+///
+/// # Examples
+/// ```
+/// use dlopen_rs::{set_unknown_reloc_policy, UnknownRelocPolicy};
+/// set_unknown_reloc_policy(UnknownRelocPolicy::Skip);
+/// ```
+pub fn set_unknown_reloc_policy(policy: UnknownRelocPolicy) {
+    *POLICY.write() = policy;
+}
+
+#[inline]
+pub(crate) fn handle(rela: &ElfRela, lib: &ElfDylib) -> bool {
+    match &*POLICY.read() {
+        UnknownRelocPolicy::Fail => {
+            log::error!(
+                "Relocating dylib [{}] failed! Unknown relocation type [{}] at offset [0x{:x}]",
+                lib.name(),
+                rela.r_type(),
+                rela.r_offset()
+            );
+            false
+        }
+        UnknownRelocPolicy::Skip => {
+            log::warn!(
+                "Skip the relocation of type [{}] at offset [0x{:x}] in dylib [{}]",
+                rela.r_type(),
+                rela.r_offset(),
+                lib.name()
+            );
+            true
+        }
+        UnknownRelocPolicy::Custom(f) => f(rela, lib.base()),
+    }
+}
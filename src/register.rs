@@ -1,22 +1,94 @@
-use crate::{Dylib, OpenFlags};
-use alloc::{borrow::ToOwned, boxed::Box, string::String, sync::Arc};
-use core::marker::PhantomData;
+use crate::{
+    event::{emit, LoadEvent},
+    loader::SONAME_ID,
+    Dylib, OpenFlags,
+};
+use alloc::{borrow::ToOwned, boxed::Box, string::String, sync::Arc, vec, vec::Vec};
+use core::{
+    marker::PhantomData,
+    sync::atomic::{AtomicUsize, Ordering},
+};
 use elf_loader::CoreComponent;
 use indexmap::IndexMap;
 use spin::{Lazy, RwLock};
 
-pub(crate) const IS_RELOCATED: u8 = u8::MAX;
+type UnloadVeto = Box<dyn Fn(&Dylib<'_>) -> bool + Send + Sync>;
+
+static UNLOAD_VETO: RwLock<Option<UnloadVeto>> = RwLock::new(None);
+
+/// Set a function that is called when the last handle of a dynamic library is dropped or closed, before the library is unloaded.
+/// If it returns `true`, the library stays resident as if it was loaded with `OpenFlags::RTLD_NODELETE`,
+/// e.g. because the host still holds callbacks into it. It replaces the previously set function.
+///
+/// The function is only called for the library whose handle is dropped, not for its dependencies, which stay resident
+/// along with it. A library kept by the function is still registered, so it is consulted again the next time a handle
+/// of the library is dropped.
+///
+/// # Note
+/// The function is called while the handle is being dropped, so it must not load or unload libraries.
+///
+/// # Examples
+/// ```no_run
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// static LIVE_CALLBACKS: AtomicUsize = AtomicUsize::new(0);
+///
+/// dlopen_rs::set_unload_veto(|lib| {
+///     lib.name().ends_with("libplugin.so") && LIVE_CALLBACKS.load(Ordering::Acquire) != 0
+/// });
+/// ```
+pub fn set_unload_veto(veto: impl Fn(&Dylib<'_>) -> bool + Send + Sync + 'static) {
+    *UNLOAD_VETO.write() = Some(Box::new(veto));
+}
+
+/// Remove the function set by [`set_unload_veto`].
+pub fn clear_unload_veto() {
+    *UNLOAD_VETO.write() = None;
+}
+
+#[inline]
+fn is_vetoed(dylib: &Dylib<'_>) -> bool {
+    let vetoed = UNLOAD_VETO.read().as_ref().is_some_and(|veto| veto(dylib));
+    if vetoed {
+        log::info!(
+            "The unloading of dylib [{}] is vetoed",
+            dylib.inner.shortname()
+        );
+    }
+    vetoed
+}
 
 impl Drop for Dylib<'_> {
     fn drop(&mut self) {
         if self.flags.contains(OpenFlags::RTLD_NODELETE) {
+            if self.flags.contains(OpenFlags::CUSTOM_NOT_REGISTER) {
+                // 未注册的库没有被全局持有，故意泄漏一个引用，使库及其依赖的映射永远不会被释放
+                core::mem::forget(self.inner.clone());
+                core::mem::forget(self.deps.clone());
+            }
             return;
         } else if self.flags.contains(OpenFlags::CUSTOM_NOT_REGISTER) {
+            // 未注册的库不在全局的all/global中，只有最后一个Dylib被销毁时才调用fini
+            if self
+                .deps
+                .as_ref()
+                .is_some_and(|deps| Arc::strong_count(deps) != 1)
+            {
+                return;
+            }
+            if is_vetoed(self) {
+                // 与RTLD_NODELETE相同，泄漏一个引用使库保持映射
+                core::mem::forget(self.inner.clone());
+                core::mem::forget(self.deps.clone());
+                return;
+            }
             log::debug!(
                 "Call the fini function from the dylib [{}]",
                 self.inner.shortname()
             );
             unsafe { self.inner.call_fini() };
+            emit(LoadEvent::Destroyed {
+                name: self.inner.shortname(),
+            });
             return;
         }
         let ref_count = self.inner.strong_count();
@@ -24,19 +96,26 @@ impl Drop for Dylib<'_> {
         let threshold =
             2 + self.deps.is_some() as usize + self.flags.contains(OpenFlags::RTLD_GLOBAL) as usize;
         if ref_count == threshold {
+            // 被否决的库仍由全局持有，不需要额外处理
+            if is_vetoed(self) {
+                return;
+            }
             log::info!("Destroying dylib [{}]", self.inner.shortname());
             log::debug!(
                 "Call the fini function from the dylib [{}]",
                 self.inner.shortname()
             );
             unsafe { self.inner.call_fini() };
-            let mut lock = MANAGER.write();
-            lock.all.shift_remove(self.inner.shortname());
+            emit(LoadEvent::Destroyed {
+                name: self.inner.shortname(),
+            });
+            let mut lock = self.manager.write();
+            lock.all.shift_remove(registry_name(&self.inner));
             if self.flags.contains(OpenFlags::RTLD_GLOBAL) {
-                lock.global.shift_remove(self.inner.shortname());
+                lock.remove_global(registry_name(&self.inner));
             }
             for dep in self.deps.as_ref().unwrap().iter().skip(1) {
-                let dep_threshold = if let Some(lib) = lock.all.get(dep.shortname()) {
+                let dep_threshold = if let Some(lib) = lock.all.get(registry_name(dep)) {
                     if lib.flags.contains(OpenFlags::RTLD_NODELETE) {
                         continue;
                     }
@@ -52,8 +131,11 @@ impl Drop for Dylib<'_> {
                         dep.shortname()
                     );
                     unsafe { dep.call_fini() };
-                    lock.all.shift_remove(dep.shortname());
-                    lock.global.shift_remove(self.inner.shortname());
+                    emit(LoadEvent::Destroyed {
+                        name: dep.shortname(),
+                    });
+                    lock.all.shift_remove(registry_name(dep));
+                    lock.remove_global(registry_name(dep));
                 }
             }
         }
@@ -65,9 +147,6 @@ pub(crate) struct GlobalDylib {
     inner: CoreComponent,
     flags: OpenFlags,
     deps: Option<Arc<Box<[CoreComponent]>>>,
-    pub(crate) new_idx: u8,
-    #[allow(unused)]
-    pub(crate) is_mark: bool,
 }
 
 unsafe impl Send for GlobalDylib {}
@@ -75,12 +154,13 @@ unsafe impl Sync for GlobalDylib {}
 
 impl GlobalDylib {
     #[inline]
-    pub(crate) fn get_dylib(&self) -> Dylib<'static> {
+    pub(crate) fn get_dylib(&self, manager: &'static RwLock<Manager>) -> Dylib<'static> {
         debug_assert!(self.deps.is_some());
         Dylib {
             inner: self.inner.clone(),
             flags: self.flags,
             deps: self.deps.clone(),
+            manager,
             _marker: PhantomData,
         }
     }
@@ -111,27 +191,123 @@ impl GlobalDylib {
     }
 }
 
+/// The id of a link-map namespace, see `ElfLibrary::dlmopen`.
+pub type Lmid = isize;
+/// The namespace used by `dlopen` and the libraries loaded by the program itself.
+pub const LM_ID_BASE: Lmid = 0;
+/// Create a new namespace when passed to `ElfLibrary::dlmopen`.
+pub const LM_ID_NEWLM: Lmid = -1;
+
 pub(crate) struct Manager {
+    pub(crate) nsid: Lmid,
+    /// 前`preloaded`个global库是预加载的库
+    pub(crate) preloaded: usize,
     pub(crate) all: IndexMap<String, GlobalDylib>,
     pub(crate) global: IndexMap<String, CoreComponent>,
 }
 
+impl Manager {
+    #[inline]
+    pub(crate) fn remove_global(&mut self, shortname: &str) {
+        if let Some((idx, _, _)) = self.global.shift_remove_full(shortname) {
+            if idx < self.preloaded {
+                self.preloaded -= 1;
+            }
+        }
+    }
+}
+
 pub(crate) static MANAGER: Lazy<RwLock<Manager>> = Lazy::new(|| {
     RwLock::new(Manager {
+        nsid: LM_ID_BASE,
+        preloaded: 0,
         all: IndexMap::new(),
         global: IndexMap::new(),
     })
 });
 
+// 命名空间不会被回收，它们的数量通常很少
+static NAMESPACES: Lazy<RwLock<Vec<&'static RwLock<Manager>>>> =
+    Lazy::new(|| RwLock::new(vec![&*MANAGER]));
+
+/// The number of libraries registered by `init`. They are loaded by the system dynamic linker
+/// and can not be loaded twice, so every namespace shares them.
+pub(crate) static SYSTEM_LIB_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+#[inline]
+pub(crate) fn namespace(nsid: Lmid) -> Option<&'static RwLock<Manager>> {
+    usize::try_from(nsid)
+        .ok()
+        .and_then(|idx| NAMESPACES.read().get(idx).copied())
+}
+
+#[inline]
+pub(crate) fn namespaces() -> Vec<&'static RwLock<Manager>> {
+    NAMESPACES.read().clone()
+}
+
+pub(crate) fn new_namespace() -> &'static RwLock<Manager> {
+    let mut namespaces = NAMESPACES.write();
+    let base = MANAGER.read();
+    let system_libs = SYSTEM_LIB_COUNT.load(Ordering::Acquire);
+    let mut manager = Manager {
+        nsid: namespaces.len() as Lmid,
+        preloaded: 0,
+        all: IndexMap::new(),
+        global: IndexMap::new(),
+    };
+    for (name, lib) in base.all.iter().take(system_libs) {
+        manager.all.insert(name.clone(), lib.clone());
+        if let Some(core) = base.global.get(name) {
+            manager.global.insert(name.clone(), core.clone());
+        }
+    }
+    log::debug!("Create a new namespace [{}]", manager.nsid);
+    let manager: &'static RwLock<Manager> = Box::leak(Box::new(RwLock::new(manager)));
+    namespaces.push(manager);
+    manager
+}
+
+/// Get the name used to register the library, it prefers `DT_SONAME` over the file name,
+/// because `DT_NEEDED` refers to the libraries by their `DT_SONAME`.
+#[inline]
+pub(crate) fn registry_name(core: &CoreComponent) -> &str {
+    core.user_data()
+        .get(SONAME_ID)
+        .and_then(|data| data.downcast_ref::<String>())
+        .map(|soname| soname.as_str())
+        .unwrap_or(core.shortname())
+}
+
+/// Get the libraries currently held by dlopen-rs in the default namespace, in load order.
+///
+/// It includes the libraries registered by `init` and those loaded with `dlopen` and `relocate`,
+/// but not those loaded with `CUSTOM_NOT_REGISTER` or into other namespaces. The returned handles keep the libraries alive,
+/// and the internal lock is released before returning.
+///
+/// # Examples
+/// ```no_run
+/// for lib in dlopen_rs::loaded_libraries() {
+///     println!("{} at 0x{:x}", lib.name(), lib.base());
+/// }
+/// ```
+pub fn loaded_libraries() -> Vec<Dylib<'static>> {
+    MANAGER
+        .read()
+        .all
+        .values()
+        .filter(|lib| lib.deps.is_some())
+        .map(|lib| lib.get_dylib(&MANAGER))
+        .collect()
+}
+
 pub(crate) fn register(
     core: CoreComponent,
     flags: OpenFlags,
     deps: Option<Arc<Box<[CoreComponent]>>>,
     manager: &mut Manager,
-    is_mark: bool,
-    new_idx: Option<u8>,
 ) {
-    let shortname = core.shortname().to_owned();
+    let shortname = registry_name(&core).to_owned();
     log::debug!(
         "Trying to register a library. Name: [{}] flags:[{:?}]",
         shortname,
@@ -140,8 +316,6 @@ pub(crate) fn register(
     manager.all.insert(
         shortname.to_owned(),
         GlobalDylib {
-            new_idx: new_idx.unwrap_or(u8::MAX),
-            is_mark,
             inner: core.clone(),
             flags,
             deps,
@@ -152,15 +326,58 @@ pub(crate) fn register(
     }
 }
 
+/// A resolver for the global scope, see [`with_global_scope`].
+#[cfg(feature = "std")]
+pub type GlobalScope = Arc<dyn Fn(&str) -> Option<*const ()> + Send + Sync>;
+
+#[cfg(feature = "std")]
+static GLOBAL_SCOPE: RwLock<Option<GlobalScope>> = RwLock::new(None);
+
+/// Get the resolver installed by [`with_global_scope`]. `None` means the default global scope is used,
+/// which consists of the libraries opened with `RTLD_GLOBAL`.
+#[cfg(feature = "std")]
+pub fn global_scope() -> Option<GlobalScope> {
+    GLOBAL_SCOPE.read().clone()
+}
+
+/// Replace the global scope used by lazy binding with `scope` while `body` runs, then restore the previous one.
+///
+/// The builtin symbols are still resolved first. The global scope is shared by all threads,
+/// so lazy binding in other threads also uses `scope` during `body`.
+///
+/// # Examples
+/// ```no_run
+/// # use dlopen_rs::{ElfLibrary, OpenFlags};
+/// use std::sync::Arc;
+///
+/// let lib = ElfLibrary::dlopen("/path/to/awesome.module", OpenFlags::RTLD_LAZY).unwrap();
+/// let run = unsafe { lib.get::<extern "C" fn()>("run").unwrap() };
+/// // Deny every symbol that is not resolved yet
+/// dlopen_rs::with_global_scope(Arc::new(|_| None), || run());
+/// ```
+#[cfg(feature = "std")]
+pub fn with_global_scope<R>(scope: GlobalScope, body: impl FnOnce() -> R) -> R {
+    struct Restore(Option<GlobalScope>);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            *GLOBAL_SCOPE.write() = self.0.take();
+        }
+    }
+    let _restore = Restore(GLOBAL_SCOPE.write().replace(scope));
+    body()
+}
+
 #[cfg(feature = "std")]
 pub(crate) fn global_find(name: &str) -> Option<*const ()> {
     log::debug!("Lazy Binding [{}]", name);
-    crate::loader::builtin::BUILTIN
-        .get(name)
-        .copied()
-        .or(MANAGER
+    crate::loader::builtin::find(name).or_else(|| {
+        if let Some(scope) = global_scope() {
+            return scope(name);
+        }
+        MANAGER
             .read()
             .global
             .values()
-            .find_map(|lib| unsafe { lib.get::<()>(name).map(|sym| sym.into_raw()) }))
+            .find_map(|lib| unsafe { lib.get::<()>(name).map(|sym| sym.into_raw()) })
+    })
 }
@@ -0,0 +1,23 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags, OsAbi};
+
+// glibc带有NT_GNU_ABI_TAG，记录了它需要的最低内核版本
+#[test]
+fn read_abi_tag_of_libc() {
+    dlopen_rs::init();
+    let libc = ElfLibrary::load_existing("libc.so.6").unwrap();
+    let Some((OsAbi::Linux, [major, _, _])) = libc.abi_tag() else {
+        panic!("libc.so.6 has no Linux ABI tag");
+    };
+    assert!(major >= 2);
+}
+
+#[test]
+fn library_without_abi_tag() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("abi_tag");
+    let path = common::build_lib(&dir, "libnoabitag.so", "int value(void) { return 1; }", &[]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(lib.abi_tag().is_none());
+}
@@ -0,0 +1,26 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags, PluginAbort};
+
+// 插件调用abort后，宿主通过catch_abort恢复
+#[test]
+fn host_recovers_from_abort() {
+    dlopen_rs::init();
+    dlopen_rs::install_abort_handler();
+    let dir = common::fixture_dir("abort");
+    let path = common::build_lib(
+        &dir,
+        "libaborts.so",
+        r#"
+        #include <stdlib.h>
+        int run(int fail) { if (fail) abort(); return 1; }
+        "#,
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    let run = unsafe { lib.get::<extern "C-unwind" fn(i32) -> i32>("run").unwrap() };
+    assert_eq!(dlopen_rs::catch_abort(|| run(0)), Ok(1));
+    assert_eq!(dlopen_rs::catch_abort(|| run(1)), Err(PluginAbort::Abort));
+    // 恢复之后插件仍然可以使用
+    assert_eq!(dlopen_rs::catch_abort(|| run(0)), Ok(1));
+}
@@ -0,0 +1,31 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// 用另一个库中的数据符号的地址初始化全局变量，会产生绝对地址重定位：
+// x86_64上是R_X86_64_64，aarch64上是R_AARCH64_ABS64，riscv64上是R_RISCV_64
+#[test]
+fn absolute_relocation_adds_the_addend() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("absolute_relocation");
+    common::build_lib(&dir, "libabsdata.so", "int abs_data[2] = { 3, 4 };", &[]);
+    let path = common::build_lib(
+        &dir,
+        "libabsuser.so",
+        &format!(
+            "{}extern int abs_data[2]; int *abs_ptr = &abs_data[1]; int *const abs_const = &abs_data[1];",
+            common::PLT_CALL
+        ),
+        &["-Wl,--no-as-needed", "-labsdata"],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    // 符号解引用得到的是变量的地址
+    let abs_data = unsafe { *lib.get::<*const i32>("abs_data").unwrap() };
+    let abs_ptr = unsafe { **lib.get::<*const *const i32>("abs_ptr").unwrap() };
+    // S + A，A是abs_data[1]相对于abs_data的偏移
+    assert_eq!(abs_ptr, abs_data.wrapping_add(1));
+    assert_eq!(unsafe { *abs_ptr }, 4);
+    // abs_const位于RELRO中，在elf_loader重定位后已经是只读的
+    let abs_const = unsafe { **lib.get::<*const *const i32>("abs_const").unwrap() };
+    assert_eq!(abs_const, abs_ptr);
+}
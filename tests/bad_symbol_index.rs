@@ -0,0 +1,47 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, Error, OpenFlags};
+
+#[test]
+fn reject_out_of_range_symbol_index() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("bad_symbol_index");
+    let path = common::build_lib(
+        &dir,
+        "libbadindex.so",
+        "int counter; int bump(void) { return ++counter; }",
+        &[],
+    );
+    let mut bytes = std::fs::read(path).unwrap();
+    // 将第一个带符号的重定位项的符号索引改为超出动态符号表的值
+    let rela = common::relocations(&bytes)
+        .into_iter()
+        .find(|rela| common::rela_info(&bytes, *rela) >> 32 != 0)
+        .unwrap();
+    let info = (0xffff << 32) | (common::rela_info(&bytes, rela) & 0xffff_ffff);
+    common::set_rela_info(&mut bytes, rela, info);
+    let res = ElfLibrary::from_binary(&bytes, "libbadindex.so", OpenFlags::CUSTOM_NOT_REGISTER);
+    assert!(matches!(res, Err(Error::FindSymbolError { .. })));
+}
+
+// 哈希表头部中的nbucket或bloom_size使bucket数组超出库的映射范围时返回错误，而不是越界读取
+#[test]
+fn reject_out_of_range_gnu_hash() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("bad_gnu_hash");
+    let path = common::build_lib(
+        &dir,
+        "libbadhash.so",
+        "int counter; int bump(void) { return ++counter; }",
+        &["-Wl,--hash-style=gnu"],
+    );
+    let bytes = std::fs::read(path).unwrap();
+    for idx in [0, 2] {
+        let mut bytes = bytes.clone();
+        common::set_gnu_hash_word(&mut bytes, idx, 0x4000_0000);
+        let res = ElfLibrary::from_binary(&bytes, "libbadhash.so", OpenFlags::CUSTOM_NOT_REGISTER);
+        let err = res.err().unwrap();
+        assert!(matches!(err, Error::FindSymbolError { .. }));
+        assert!(err.to_string().contains("hash table"), "{}", err);
+    }
+}
@@ -0,0 +1,60 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const SRC: &str = r#"
+    extern int puts(const char *);
+    int hello(void) { return puts("hello"); }
+"#;
+
+// DF_BIND_NOW优先于RTLD_LAZY
+#[test]
+fn bind_now_overrides_lazy() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("bind_now");
+    let now = common::build_lib(&dir, "libnow.so", SRC, &["-Wl,-z,now"]);
+    let lazy = common::build_lib(&dir, "liblazy.so", SRC, &["-Wl,-z,lazy"]);
+    let flags = OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_LAZY;
+    let now = ElfLibrary::dlopen(now, flags).unwrap();
+    assert!(!now.is_lazy());
+    let lazy = ElfLibrary::dlopen(lazy, flags).unwrap();
+    assert!(lazy.is_lazy());
+}
+
+// 头部中的偏移和数量来自文件，越界时返回错误
+#[test]
+fn corrupted_headers_are_rejected() {
+    let dir = common::fixture_dir("bind_now_corrupted");
+    let path = common::build_lib(&dir, "libcorrupted.so", SRC, &[]);
+    let bytes = std::fs::read(path).unwrap();
+
+    let mut phoff = bytes.clone();
+    phoff[0x20..0x28].copy_from_slice(&u64::MAX.to_ne_bytes());
+    assert!(ElfLibrary::from_binary(&phoff, "libcorrupted.so", OpenFlags::RTLD_LOCAL).is_err());
+
+    let mut class = bytes.clone();
+    class[4] = 1;
+    assert!(ElfLibrary::from_binary(&class, "libcorrupted.so", OpenFlags::RTLD_LOCAL).is_err());
+
+    assert!(
+        ElfLibrary::from_binary(&bytes[..0x30], "libcorrupted.so", OpenFlags::RTLD_LOCAL).is_err()
+    );
+}
+
+#[test]
+fn endianness_mismatch_is_a_header_error() {
+    let dir = common::fixture_dir("bind_now_endianness");
+    let path = common::build_lib(&dir, "libendianness.so", SRC, &[]);
+    let mut bytes = std::fs::read(path).unwrap();
+    // EI_DATA
+    bytes[5] = if cfg!(target_endian = "little") { 2 } else { 1 };
+    let err = ElfLibrary::from_binary(&bytes, "libendianness.so", OpenFlags::RTLD_LOCAL)
+        .err()
+        .unwrap();
+    assert!(
+        matches!(err, dlopen_rs::Error::LoaderError { .. })
+            && err.to_string().ends_with("endianness mismatch"),
+        "{:?}",
+        err
+    );
+}
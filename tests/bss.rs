@@ -0,0 +1,28 @@
+#![cfg(not(feature = "mmap"))]
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const BSS_SIZE: usize = 4 << 20;
+
+// 不使用mmap时库被加载到堆上，堆内存不可执行，所以库中不能有需要执行的代码(包括crt中的初始化函数)，
+// 这里直接通过符号地址检查bss
+#[test]
+fn large_bss_is_zeroed() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("bss");
+    let path = common::build_lib(
+        &dir,
+        "libbigbss.so",
+        &format!("unsigned char big[{}];", BSS_SIZE),
+        &["-nostartfiles"],
+    );
+    // 释放一块mmap分配的内存后glibc会提高mmap的阈值，之后的大块内存从堆上分配，
+    // 这样加载库时会重用下面被弄脏的内存
+    drop(vec![0u8; 4 * BSS_SIZE]);
+    drop(vec![0xffu8; 2 * BSS_SIZE]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let big = unsafe { lib.get::<*const u8>("big").unwrap() };
+    let big = unsafe { core::slice::from_raw_parts(*big, BSS_SIZE) };
+    assert!(big.iter().all(|byte| *byte == 0));
+}
@@ -0,0 +1,14 @@
+use dlopen_rs::capabilities;
+
+#[test]
+fn capabilities_match_the_build() {
+    let caps = capabilities();
+    assert_eq!(caps.arch, std::env::consts::ARCH);
+    assert_eq!(caps.mmap, cfg!(feature = "mmap"));
+    assert_eq!(caps.tls, cfg!(feature = "tls"));
+    assert_eq!(caps.version, env!("CARGO_PKG_VERSION"));
+    assert_eq!(
+        caps.page_size,
+        Some(unsafe { libc::sysconf(libc::_SC_PAGESIZE) } as usize)
+    );
+}
@@ -0,0 +1,90 @@
+#![allow(dead_code)]
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// elf_loader在库没有.rela.plt时会忽略.rela.dyn中的重定位，
+/// 不调用外部函数的测试库需要加上这段代码来产生一个PLT重定位
+pub const PLT_CALL: &str = "#include <unistd.h>\nint plt_call(void) { return getpid(); }\n";
+
+/// 返回测试用的目录，不同的测试使用不同的目录，避免互相覆盖
+pub fn fixture_dir(test: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(test);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+/// 用gcc将C代码编译成`dir`下名为`name`的动态库，`args`会原样传给gcc。
+/// 依赖库从`dir`中查找（DT_RPATH为`$ORIGIN`）
+pub fn build_lib(dir: &Path, name: &str, src: &str, args: &[&str]) -> PathBuf {
+    let src_path = dir.join(name).with_extension("c");
+    std::fs::write(&src_path, src).unwrap();
+    let out = dir.join(name);
+    let status = Command::new("gcc")
+        .args(["-shared", "-fPIC", "-o"])
+        .arg(&out)
+        .arg(&src_path)
+        .arg(format!("-L{}", dir.display()))
+        .args(["-Wl,-rpath,$ORIGIN", "-Wl,--disable-new-dtags"])
+        .args(args)
+        .status()
+        .expect("failed to run gcc");
+    assert!(status.success(), "failed to build {}", name);
+    out
+}
+
+fn read_u64(bytes: &[u8], off: usize) -> u64 {
+    u64::from_le_bytes(bytes[off..off + 8].try_into().unwrap())
+}
+
+/// 按节头表的顺序返回64位elf文件中类型为`sh_type`的节在文件中的偏移和大小
+pub fn sections(bytes: &[u8], sh_type: u32) -> Vec<(usize, usize)> {
+    let shoff = read_u64(bytes, 0x28) as usize;
+    let shentsize = u16::from_le_bytes([bytes[0x3a], bytes[0x3b]]) as usize;
+    let shnum = u16::from_le_bytes([bytes[0x3c], bytes[0x3d]]) as usize;
+    (0..shnum)
+        .map(|i| shoff + i * shentsize)
+        .filter(|shdr| u32::from_le_bytes(bytes[shdr + 4..shdr + 8].try_into().unwrap()) == sh_type)
+        .map(|shdr| {
+            (
+                read_u64(bytes, shdr + 0x18) as usize,
+                read_u64(bytes, shdr + 0x20) as usize,
+            )
+        })
+        .collect()
+}
+
+/// 按节头表的顺序返回64位elf文件中所有`Elf64_Rela`重定位项在文件中的偏移
+pub fn relocations(bytes: &[u8]) -> Vec<usize> {
+    const SHT_RELA: u32 = 4;
+    sections(bytes, SHT_RELA)
+        .into_iter()
+        .flat_map(|(offset, size)| (offset..offset + size).step_by(24))
+        .collect()
+}
+
+/// 返回`.gnu.hash`在文件中的偏移
+pub fn gnu_hash(bytes: &[u8]) -> usize {
+    const SHT_GNU_HASH: u32 = 0x6ffffff6;
+    sections(bytes, SHT_GNU_HASH)[0].0
+}
+
+/// 修改`.gnu.hash`头部的第`idx`个字
+pub fn set_gnu_hash_word(bytes: &mut [u8], idx: usize, value: u32) {
+    let off = gnu_hash(bytes) + idx * 4;
+    bytes[off..off + 4].copy_from_slice(&value.to_le_bytes());
+}
+
+/// 读取重定位项的`r_info`
+pub fn rela_info(bytes: &[u8], rela: usize) -> u64 {
+    read_u64(bytes, rela + 8)
+}
+
+/// 修改重定位项的`r_offset`
+pub fn set_rela_offset(bytes: &mut [u8], rela: usize, offset: u64) {
+    bytes[rela..rela + 8].copy_from_slice(&offset.to_le_bytes());
+}
+
+/// 修改重定位项的`r_info`
+pub fn set_rela_info(bytes: &mut [u8], rela: usize, info: u64) {
+    bytes[rela + 8..rela + 16].copy_from_slice(&info.to_le_bytes());
+}
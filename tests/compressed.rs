@@ -0,0 +1,80 @@
+#![cfg(feature = "compression")]
+mod common;
+
+use dlopen_rs::{Codec, ElfLibrary, Error, OpenFlags};
+use std::path::Path;
+use std::process::Command;
+
+fn build(test: &str) -> Vec<u8> {
+    let dir = common::fixture_dir(test);
+    let path = common::build_lib(
+        &dir,
+        "libcompressed.so",
+        &format!("{}int value(void) {{ return 7; }}", common::PLT_CALL),
+        &[],
+    );
+    std::fs::read(path).unwrap()
+}
+
+// 用命令行工具压缩，保证与常见的.so.gz和.so.zst格式一致
+fn compress(tool: &str, path: &Path, bytes: &[u8]) -> Vec<u8> {
+    std::fs::write(path, bytes).unwrap();
+    let out = Command::new(tool).arg("-c").arg(path).output().unwrap();
+    assert!(out.status.success(), "failed to run {}", tool);
+    out.stdout
+}
+
+fn load(bytes: &[u8], codec: Codec) -> dlopen_rs::Result<i32> {
+    let lib = ElfLibrary::from_compressed(
+        bytes,
+        "libcompressed.so",
+        OpenFlags::CUSTOM_NOT_REGISTER,
+        codec,
+    )?
+    .relocate(&[])?;
+    let value = unsafe { lib.get::<extern "C" fn() -> i32>("value")? };
+    Ok(value())
+}
+
+#[cfg(feature = "gzip")]
+#[test]
+fn load_gzip() {
+    dlopen_rs::init();
+    let bytes = build("compressed_gzip");
+    let dir = common::fixture_dir("compressed_gzip");
+    let gz = compress("gzip", &dir.join("lib.so"), &bytes);
+    assert_eq!(load(&gz, Codec::Gzip).unwrap(), 7);
+    // 截断的数据和损坏的尾部都返回错误
+    let truncated = load(&gz[..gz.len() / 2], Codec::Gzip);
+    assert!(matches!(truncated, Err(Error::DecompressError { .. })));
+    let mut corrupted = gz.clone();
+    let crc = corrupted.len() - 8;
+    corrupted[crc] ^= 1;
+    let corrupted = load(&corrupted, Codec::Gzip);
+    assert!(matches!(corrupted, Err(Error::DecompressError { .. })));
+}
+
+// 由多个成员连接而成的gzip文件
+#[cfg(feature = "gzip")]
+#[test]
+fn load_multi_member_gzip() {
+    dlopen_rs::init();
+    let bytes = build("compressed_gzip_members");
+    let dir = common::fixture_dir("compressed_gzip_members");
+    let (first, second) = bytes.split_at(bytes.len() / 2);
+    let mut gz = compress("gzip", &dir.join("first"), first);
+    gz.extend(compress("gzip", &dir.join("second"), second));
+    assert_eq!(load(&gz, Codec::Gzip).unwrap(), 7);
+}
+
+#[cfg(feature = "zstd")]
+#[test]
+fn load_zstd() {
+    dlopen_rs::init();
+    let bytes = build("compressed_zstd");
+    let dir = common::fixture_dir("compressed_zstd");
+    let zst = compress("zstd", &dir.join("lib.so"), &bytes);
+    assert_eq!(load(&zst, Codec::Zstd).unwrap(), 7);
+    let truncated = load(&zst[..zst.len() / 2], Codec::Zstd);
+    assert!(matches!(truncated, Err(Error::DecompressError { .. })));
+}
@@ -0,0 +1,70 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::sync::{Arc, Barrier};
+
+const THREADS: usize = 8;
+const ROUNDS: usize = 16;
+
+// 多个线程同时加载依赖同一个库的不同库，依赖库只能有一个实例，并且只初始化一次
+#[test]
+fn concurrent_loads_share_one_dependency() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("concurrent");
+    common::build_lib(
+        &dir,
+        "libcounter.so",
+        r#"
+        static int counter;
+        int *counter_addr(void) { return &counter; }
+        "#,
+        &[],
+    );
+    for round in 0..ROUNDS {
+        common::build_lib(
+            &dir,
+            &format!("libshared{round}.so"),
+            r#"
+            extern int *counter_addr(void);
+            static int value;
+            __attribute__((constructor)) static void init(void) { ++*counter_addr(); }
+            int *shared_value(void) { return &value; }
+            "#,
+            &["-lcounter"],
+        );
+        for i in 0..THREADS {
+            common::build_lib(
+                &dir,
+                &format!("libtop{round}_{i}.so"),
+                r#"
+                extern int *shared_value(void);
+                int *top_value(void) { return shared_value(); }
+                "#,
+                &[&format!("-lshared{round}")],
+            );
+        }
+    }
+    let counter = ElfLibrary::dlopen(dir.join("libcounter.so"), OpenFlags::RTLD_LOCAL).unwrap();
+    let counter_addr = unsafe { counter.get::<fn() -> *const i32>("counter_addr").unwrap() };
+    for round in 0..ROUNDS {
+        let barrier = Arc::new(Barrier::new(THREADS));
+        let handles: Vec<_> = (0..THREADS)
+            .map(|i| {
+                let path = dir.join(format!("libtop{round}_{i}.so"));
+                let barrier = barrier.clone();
+                std::thread::spawn(move || {
+                    barrier.wait();
+                    let top = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+                    let top_value = unsafe { top.get::<fn() -> usize>("top_value").unwrap() };
+                    let value = top_value();
+                    // 库需要一直保持加载，否则依赖库可能被卸载后重新加载
+                    core::mem::forget(top);
+                    value
+                })
+            })
+            .collect();
+        let values: Vec<usize> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+        assert!(values.iter().all(|v| *v == values[0]));
+    }
+    assert_eq!(unsafe { *counter_addr() }, ROUNDS as i32);
+}
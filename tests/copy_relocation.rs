@@ -0,0 +1,35 @@
+#![cfg(target_arch = "x86_64")]
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const R_X86_64_64: u64 = 1;
+const R_X86_64_COPY: u64 = 5;
+
+// 动态库中不会产生COPY重定位，所以将p的R_X86_64_64重定位改为COPY，
+// 从libcopydata.so的host_data中复制8个字节到p
+#[test]
+fn copy_relocation_copies_the_data() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("copy_relocation");
+    common::build_lib(&dir, "libcopydata.so", "int host_data[2] = { 1, 2 };", &[]);
+    let path = common::build_lib(
+        &dir,
+        "libcopyuser.so",
+        &format!("{}int host_data[2]; int *p = host_data;", common::PLT_CALL),
+        &["-Wl,--no-as-needed", "-lcopydata"],
+    );
+    let mut bytes = std::fs::read(&path).unwrap();
+    let rela = common::relocations(&bytes)
+        .into_iter()
+        .find(|rela| common::rela_info(&bytes, *rela) & 0xffff_ffff == R_X86_64_64)
+        .unwrap();
+    let info = common::rela_info(&bytes, rela) & !0xffff_ffff | R_X86_64_COPY;
+    common::set_rela_info(&mut bytes, rela, info);
+    let copy = path.with_file_name("libcopy.so");
+    std::fs::write(&copy, bytes).unwrap();
+
+    let lib = ElfLibrary::dlopen(copy, OpenFlags::RTLD_LOCAL).unwrap();
+    let p = unsafe { lib.get::<*const [i32; 2]>("p").unwrap() };
+    assert_eq!(unsafe { **p }, [1, 2]);
+}
@@ -0,0 +1,32 @@
+#![cfg(target_arch = "x86_64")]
+mod common;
+
+use dlopen_rs::{CpuFeatures, ElfLibrary, OpenFlags};
+
+const SRC: &str = "int value(void) { return 1; }";
+
+// crt文件没有GNU_PROPERTY，链接时会清除特性，所以用-nostartfiles构建
+#[test]
+fn read_cet_property() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("cpu_features");
+    let cet = common::build_lib(
+        &dir,
+        "libcet.so",
+        SRC,
+        &["-fcf-protection=full", "-nostartfiles"],
+    );
+    let plain = common::build_lib(
+        &dir,
+        "libnocet.so",
+        SRC,
+        &["-fcf-protection=none", "-nostartfiles"],
+    );
+    let cet = ElfLibrary::dlopen(cet, OpenFlags::RTLD_LOCAL).unwrap();
+    assert_eq!(
+        cet.cpu_features(),
+        CpuFeatures::X86_IBT | CpuFeatures::X86_SHSTK
+    );
+    let plain = ElfLibrary::dlopen(plain, OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(plain.cpu_features().is_empty());
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "deny-textrel")]
+mod common;
+
+use dlopen_rs::{ElfLibrary, Error, OpenFlags};
+
+// 与textrel.rs中的库相同，table位于.text中，会产生DT_TEXTREL
+const SRC: &str = r#"
+int value = 42;
+__asm__(".text\n.balign 8\n.globl table\n.type table,@object\ntable: .quad value\n.previous\n");
+extern int *table;
+int get(void) { return *table; }
+"#;
+
+#[test]
+fn refuse_text_relocations() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("deny_textrel");
+    let path = common::build_lib(
+        &dir,
+        "libdenytextrel.so",
+        &format!("{}{}", common::PLT_CALL, SRC),
+        &["-Wl,-z,notext"],
+    );
+    let res = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_NOW);
+    assert!(matches!(res, Err(Error::TextRelError { .. })));
+}
@@ -0,0 +1,53 @@
+mod common;
+
+use dlopen_rs::{Dylib, ElfLibrary, OpenFlags};
+use std::ffi::{c_void, CStr};
+
+const RTLD_DL_SYMENT: i32 = 1;
+const RTLD_DL_LINKMAP: i32 = 2;
+
+fn load(test: &str) -> (Dylib<'static>, *const c_void) {
+    dlopen_rs::init();
+    let dir = common::fixture_dir(test);
+    let path = common::build_lib(
+        &dir,
+        &format!("lib{}.so", test),
+        "int dladdr_target(void) { return 1; }",
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let addr = unsafe { lib.get::<()>("dladdr_target").unwrap().into_raw() } as *const c_void;
+    (lib, addr)
+}
+
+#[test]
+fn dladdr1_returns_symbol_entry() {
+    let (lib, addr) = load("dladdr_syment");
+
+    let mut info: libc::Dl_info = unsafe { core::mem::zeroed() };
+    let mut sym: *mut c_void = core::ptr::null_mut();
+    let ret = unsafe { dlopen_rs::dlopen::dladdr1(addr, &mut info, &mut sym, RTLD_DL_SYMENT) };
+    assert_eq!(ret, 1);
+    assert_eq!(info.dli_fbase as usize, lib.base());
+    assert_eq!(
+        unsafe { CStr::from_ptr(info.dli_sname) }.to_str().unwrap(),
+        "dladdr_target"
+    );
+    assert_eq!(info.dli_saddr as *const c_void, addr);
+    let sym = unsafe { &*(sym as *const libc::Elf64_Sym) };
+    assert_eq!(sym.st_value as usize, addr as usize - lib.base());
+}
+
+// link_map的第一个成员l_addr是库的基址
+#[cfg(feature = "debug")]
+#[test]
+fn dladdr1_returns_link_map() {
+    let (lib, addr) = load("dladdr_linkmap");
+    let mut info: libc::Dl_info = unsafe { core::mem::zeroed() };
+    let mut link_map: *mut c_void = core::ptr::null_mut();
+    let ret =
+        unsafe { dlopen_rs::dlopen::dladdr1(addr, &mut info, &mut link_map, RTLD_DL_LINKMAP) };
+    assert_eq!(ret, 1);
+    assert!(!link_map.is_null());
+    assert_eq!(unsafe { *(link_map as *const usize) }, lib.base());
+}
@@ -0,0 +1,72 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::ffi::{c_char, c_void};
+
+#[test]
+fn rtld_default_searches_the_global_scope() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("dlsym_default");
+    let path = common::build_lib(
+        &dir,
+        "libdefault.so",
+        "int dlsym_default_value(void) { return 1; }",
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL).unwrap();
+    let expected = unsafe { lib.get::<()>("dlsym_default_value").unwrap().into_raw() };
+    let found =
+        unsafe { dlopen_rs::dlopen::dlsym(core::ptr::null(), c"dlsym_default_value".as_ptr()) };
+    assert_eq!(found, expected as *const c_void);
+}
+
+// RTLD_NEXT从调用者之后加载的库中查找符号，调用者通过返回地址确定
+#[test]
+fn rtld_next_searches_after_the_caller() {
+    dlopen_rs::init();
+    unsafe {
+        dlopen_rs::register_builtin(
+            "dlsym",
+            dlopen_rs::dlopen::dlsym
+                as unsafe extern "C" fn(*const c_void, *const c_char) -> *const c_void
+                as *const (),
+        )
+    };
+    let dir = common::fixture_dir("dlsym_next");
+    let shim = common::build_lib(
+        &dir,
+        "libshim.so",
+        r#"
+        #define _GNU_SOURCE
+        #include <dlfcn.h>
+        int dlsym_next_value(void) { return 1; }
+        int call_next(void) {
+            int (*next)(void) = (int (*)(void))dlsym(RTLD_NEXT, "dlsym_next_value");
+            return next ? next() : -1;
+        }
+        "#,
+        &[],
+    );
+    let real = common::build_lib(
+        &dir,
+        "libreal.so",
+        "int dlsym_next_value(void) { return 2; }",
+        &[],
+    );
+    let shim = ElfLibrary::dlopen(shim, OpenFlags::RTLD_NOW).unwrap();
+    let _real = ElfLibrary::dlopen(real, OpenFlags::RTLD_NOW).unwrap();
+    let call_next = unsafe { shim.get::<extern "C" fn() -> i32>("call_next").unwrap() };
+    assert_eq!(call_next(), 2);
+}
+
+// dlopen(NULL)返回全局作用域的句柄，可以找到程序依赖的libc中的符号
+#[test]
+fn null_filename_opens_the_global_scope() {
+    dlopen_rs::init();
+    let handle = unsafe { dlopen_rs::dlopen::dlopen(core::ptr::null(), libc::RTLD_NOW) };
+    assert!(!handle.is_null());
+    let getpid = unsafe { dlopen_rs::dlopen::dlsym(handle, c"getpid".as_ptr()) };
+    assert!(!getpid.is_null());
+    let getpid: extern "C" fn() -> libc::pid_t = unsafe { core::mem::transmute(getpid) };
+    assert_eq!(getpid(), std::process::id() as libc::pid_t);
+}
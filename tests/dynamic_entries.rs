@@ -0,0 +1,34 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const DT_INIT: i64 = 12;
+const DT_INIT_ARRAY: i64 = 25;
+const DT_RELR: i64 = 36;
+const DT_DLOPEN_RS_HIDDEN: i64 = 0x6ffff000;
+
+// dlopen-rs隐藏的tag以原来的值返回
+#[test]
+fn hidden_tags_are_reported_as_is() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("dynamic_entries");
+    let path = common::build_lib(
+        &dir,
+        "libentries.so",
+        r#"
+        static int value;
+        static int *ptrs[] = { &value, &value };
+        __attribute__((constructor)) static void init(void) { value = 1; }
+        int get(int i) { return *ptrs[i]; }
+        "#,
+        &["-Wl,-z,pack-relative-relocs"],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let get = unsafe { lib.get::<extern "C" fn(i32) -> i32>("get").unwrap() };
+    assert_eq!(get(1), 1);
+    let tags: Vec<i64> = lib.dynamic_entries().iter().map(|(tag, _)| *tag).collect();
+    for tag in [DT_INIT, DT_INIT_ARRAY, DT_RELR] {
+        assert!(tags.contains(&tag), "{:#x} is missing", tag);
+    }
+    assert!(!tags.contains(&DT_DLOPEN_RS_HIDDEN));
+}
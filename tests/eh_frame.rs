@@ -0,0 +1,44 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::process::Command;
+
+// 用readelf读取节的地址
+fn section_addr(path: &std::path::Path, name: &str) -> usize {
+    let out = Command::new("readelf")
+        .arg("-SW")
+        .arg(path)
+        .output()
+        .unwrap();
+    let out = String::from_utf8(out.stdout).unwrap();
+    // 节的序号可能带有空格，如`[ 9]`，所以从`]`之后开始分割
+    let mut fields = out
+        .lines()
+        .filter_map(|line| line.split_once(']'))
+        .map(|(_, line)| line.split_whitespace())
+        .find(|fields| fields.clone().next() == Some(name))
+        .unwrap();
+    let addr = fields.nth(2).unwrap();
+    usize::from_str_radix(addr, 16).unwrap()
+}
+
+#[test]
+fn eh_frame_is_in_the_mapped_range() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("eh_frame");
+    let path = common::build_lib(&dir, "libehframe.so", "int value(void) { return 1; }", &[]);
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LOCAL).unwrap();
+    let (eh_frame, eh_frame_hdr) = lib.eh_frame().unwrap();
+    let base = lib.base();
+    let in_segment = |addr: usize| {
+        lib.segments().any(|phdr| {
+            let start = base + phdr.p_vaddr as usize;
+            (start..start + phdr.p_memsz as usize).contains(&addr)
+        })
+    };
+    assert!(in_segment(eh_frame));
+    assert!(in_segment(eh_frame_hdr));
+    assert_eq!(eh_frame_hdr, lib.eh_frame_range().unwrap().start);
+    assert_eq!(eh_frame - base, section_addr(&path, ".eh_frame"));
+    assert_eq!(eh_frame_hdr - base, section_addr(&path, ".eh_frame_hdr"));
+}
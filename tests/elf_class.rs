@@ -0,0 +1,22 @@
+// gcc的-m32只在x86_64上可用
+#![cfg(target_arch = "x86_64")]
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// 只支持64位的elf文件，32位的库在查找符号之前就会被拒绝，所以不会用错误的bloom字长查找符号
+#[test]
+fn reject_32_bit_object() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("elf_class");
+    let path = common::build_lib(
+        &dir,
+        "libelf32.so",
+        "int value(void) { return 1; }",
+        &["-m32", "-nostdlib"],
+    );
+    let err = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL)
+        .err()
+        .unwrap();
+    assert!(err.to_string().contains("not a 64-bit elf file"), "{}", err);
+}
@@ -0,0 +1,17 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const SRC: &str = "int value(void) { return 1; }";
+
+#[test]
+fn detect_exec_stack() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("exec_stack");
+    let exec = common::build_lib(&dir, "libexecstack.so", SRC, &["-Wl,-z,execstack"]);
+    let noexec = common::build_lib(&dir, "libnoexecstack.so", SRC, &["-Wl,-z,noexecstack"]);
+    let exec = ElfLibrary::dlopen(exec, OpenFlags::RTLD_LOCAL).unwrap();
+    let noexec = ElfLibrary::dlopen(noexec, OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(exec.wants_exec_stack());
+    assert!(!noexec.wants_exec_stack());
+}
@@ -0,0 +1,48 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::path::{Path, PathBuf};
+
+// filter和filtee中都定义了value，通过filter引用value的库应该使用filtee中的定义
+fn build_user(dir: &Path, filter: &str, filter_args: &[&str]) -> PathBuf {
+    common::build_lib(dir, "libfiltee.so", "int value(void) { return 2; }", &[]);
+    common::build_lib(dir, filter, "int value(void) { return 1; }", filter_args);
+    let lib = format!("-l{}", &filter[3..filter.len() - 3]);
+    common::build_lib(
+        dir,
+        "libfilteruser.so",
+        "int value(void); int call_value(void) { return value(); }",
+        &["-Wl,--no-as-needed", &lib],
+    )
+}
+
+#[test]
+fn filter_uses_the_filtee() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("filter");
+    let user = build_user(&dir, "libfilter.so", &["-Wl,--filter=libfiltee.so"]);
+    let lib = ElfLibrary::dlopen(user, OpenFlags::RTLD_LOCAL).unwrap();
+    let call_value = unsafe { lib.get::<extern "C" fn() -> i32>("call_value").unwrap() };
+    assert_eq!(call_value(), 2);
+}
+
+#[test]
+fn auxiliary_uses_the_filtee() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("auxiliary");
+    let user = build_user(&dir, "libauxfilter.so", &["-Wl,--auxiliary=libfiltee.so"]);
+    let lib = ElfLibrary::dlopen(user, OpenFlags::RTLD_LOCAL).unwrap();
+    let call_value = unsafe { lib.get::<extern "C" fn() -> i32>("call_value").unwrap() };
+    assert_eq!(call_value(), 2);
+}
+
+// DT_AUXILIARY的filtee不存在时使用filter自身的定义
+#[test]
+fn missing_auxiliary_falls_back_to_the_filter() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("missing_auxiliary");
+    let user = build_user(&dir, "libauxfilter.so", &["-Wl,--auxiliary=libmissing.so"]);
+    let lib = ElfLibrary::dlopen(user, OpenFlags::RTLD_LOCAL).unwrap();
+    let call_value = unsafe { lib.get::<extern "C" fn() -> i32>("call_value").unwrap() };
+    assert_eq!(call_value(), 1);
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "debug")]
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::ffi::{c_char, c_int, c_void, CStr};
+
+#[repr(C)]
+struct LinkMap {
+    l_addr: usize,
+    l_name: *const c_char,
+    l_ld: *const c_void,
+    l_next: *const LinkMap,
+    l_prev: *const LinkMap,
+}
+
+#[repr(C)]
+struct RDebug {
+    r_version: c_int,
+    r_map: *const LinkMap,
+    r_brk: usize,
+    r_state: c_int,
+}
+
+extern "C" {
+    static _r_debug: RDebug;
+}
+
+const PT_DYNAMIC: u32 = 2;
+const RT_CONSISTENT: c_int = 0;
+
+const SRC: &str = "int gdb_value(void) { return 1; }";
+
+// gdb通过_r_debug中的link_map找到动态库，dlopen加载的库也要在其中
+#[test]
+fn dlopen_adds_the_library_to_r_debug() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("gdb");
+    let path = common::build_lib(&dir, "libgdb.so", SRC, &[]);
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LOCAL).unwrap();
+    let dynamic = lib
+        .phdrs()
+        .iter()
+        .find(|phdr| phdr.p_type == PT_DYNAMIC)
+        .unwrap();
+    let debug = unsafe { &*core::ptr::addr_of!(_r_debug) };
+    assert_eq!(debug.r_state, RT_CONSISTENT);
+    let mut cur = debug.r_map;
+    let mut found = None;
+    while !cur.is_null() {
+        let map = unsafe { &*cur };
+        if !map.l_name.is_null()
+            && unsafe { CStr::from_ptr(map.l_name) }.to_str() == Ok(path.to_str().unwrap())
+        {
+            found = Some(map);
+        }
+        cur = map.l_next;
+    }
+    let map = found.expect("libgdb.so is not in the link map of _r_debug");
+    assert_eq!(map.l_addr, lib.base());
+    assert_eq!(map.l_ld as usize, lib.base() + dynamic.p_vaddr as usize);
+}
+
+// 手动测试，需要gdb：
+// ```text
+// $ cargo test --features debug --test gdb --no-run
+// $ gdb --args target/debug/deps/gdb-<hash> debug_with_gdb --ignored --test-threads=1
+// (gdb) set breakpoint pending on
+// (gdb) break gdb_value
+// (gdb) run
+// (gdb) info sharedlibrary   # 列出libgdb.so
+// (gdb) bt                   # gdb_value位于libgdb.so中
+// (gdb) finish               # 返回值为1
+// ```
+#[test]
+#[ignore = "run it under gdb"]
+fn debug_with_gdb() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("gdb_manual");
+    let path = common::build_lib(&dir, "libgdb.so", SRC, &["-g"]);
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LOCAL).unwrap();
+    let gdb_value = unsafe { lib.get::<extern "C" fn() -> i32>("gdb_value").unwrap() };
+    assert_eq!(gdb_value(), 1);
+}
@@ -0,0 +1,28 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// 插件和依赖库都导出了version，get_own只查找插件自身
+#[test]
+fn get_own_returns_the_plugin_export() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("get_own");
+    common::build_lib(
+        &dir,
+        "libowndep.so",
+        "int version(void) { return 2; } int dep_only(void) { return 3; }",
+        &[],
+    );
+    let path = common::build_lib(
+        &dir,
+        "libownplugin.so",
+        "int version(void) { return 1; }",
+        &["-Wl,--no-as-needed", "-lowndep"],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let version = unsafe { lib.get_own::<extern "C" fn() -> i32>("version").unwrap() };
+    assert_eq!(version(), 1);
+    assert!(unsafe { lib.get_own::<extern "C" fn() -> i32>("dep_only") }.is_err());
+    let dep_only = unsafe { lib.get::<extern "C" fn() -> i32>("dep_only").unwrap() };
+    assert_eq!(dep_only(), 3);
+}
@@ -0,0 +1,32 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::sync::Arc;
+
+extern "C" fn scoped_func() -> i32 {
+    5
+}
+
+// 延迟绑定时由with_global_scope安装的resolver提供scoped_func，body结束后恢复默认的全局作用域
+#[test]
+fn with_global_scope_resolves_lazy_symbols() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("global_scope");
+    let path = common::build_lib(
+        &dir,
+        "libglobalscope.so",
+        "int scoped_func(void); int call_scoped(void) { return scoped_func(); }",
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_LAZY).unwrap();
+    let call_scoped = unsafe { lib.get::<extern "C" fn() -> i32>("call_scoped").unwrap() };
+    assert!(dlopen_rs::global_scope().is_none());
+    let scope: dlopen_rs::GlobalScope =
+        Arc::new(|name| (name == "scoped_func").then_some(scoped_func as *const ()));
+    let value = dlopen_rs::with_global_scope(scope, || {
+        assert!(dlopen_rs::global_scope().is_some());
+        call_scoped()
+    });
+    assert_eq!(value, 5);
+    assert!(dlopen_rs::global_scope().is_none());
+}
@@ -0,0 +1,24 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+#[test]
+fn histogram_counts_relative_relocations() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("histogram");
+    let path = common::build_lib(
+        &dir,
+        "libhistogram.so",
+        r#"
+        extern int puts(const char *);
+        static int value;
+        int *ptrs[] = { &value, &value, &value };
+        int hello(void) { return puts("hello"); }
+        "#,
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW).unwrap();
+    let histogram = lib.relocation_histogram();
+    assert!(histogram.get("relative").is_some_and(|count| *count >= 3));
+    assert!(histogram.get("jump_slot").is_some_and(|count| *count >= 1));
+}
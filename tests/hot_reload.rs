@@ -0,0 +1,36 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// 新版本的库使用原来的依赖库进行重定位
+#[test]
+fn reload_repeatedly() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("hot_reload");
+    common::build_lib(
+        &dir,
+        "libreloaddep.so",
+        "int base(void) { return 100; }",
+        &[],
+    );
+    let versions: Vec<_> = (1..=2)
+        .map(|version| {
+            common::build_lib(
+                &dir,
+                &format!("libhotplugin.so.{}", version),
+                &format!(
+                    "int base(void); int value(void) {{ return base() + {}; }}",
+                    version
+                ),
+                &["-Wl,--no-as-needed", "-lreloaddep"],
+            )
+        })
+        .collect();
+    let mut lib = ElfLibrary::dlopen(&versions[0], OpenFlags::RTLD_LOCAL).unwrap();
+    for i in 0..10 {
+        let new_lib = lib.reload(&versions[(i + 1) % 2]).unwrap();
+        let value = unsafe { new_lib.get::<extern "C" fn() -> i32>("value").unwrap() };
+        assert_eq!(value(), 100 + ((i + 1) % 2) as i32 + 1);
+        lib = new_lib;
+    }
+}
@@ -0,0 +1,33 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+extern "C" fn fixed_time(_: *mut i64) -> i64 {
+    42
+}
+
+// 插件依赖库中对time的引用也会使用替换后的函数，即使要求延迟绑定
+#[test]
+fn dependency_resolves_interposed_symbol() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("interpose");
+    common::build_lib(
+        &dir,
+        "libinterposedep.so",
+        "#include <time.h>\nlong dep_time(void) { return time(NULL); }",
+        &[],
+    );
+    let plugin = common::build_lib(
+        &dir,
+        "libinterposeplugin.so",
+        "long dep_time(void); long plugin_time(void) { return dep_time(); }",
+        &["-Wl,--no-as-needed", "-linterposedep"],
+    );
+    let lib = ElfLibrary::builder()
+        .flags(OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_LAZY)
+        .interpose(|name| (name == "time").then_some(fixed_time as *const ()))
+        .build_from_file(plugin)
+        .unwrap();
+    let plugin_time = unsafe { lib.get::<extern "C" fn() -> i64>("plugin_time").unwrap() };
+    assert_eq!(plugin_time(), 42);
+}
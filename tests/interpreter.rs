@@ -0,0 +1,22 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// glibc的libc.so.6可以直接运行，所以带有PT_INTERP
+#[cfg(all(target_arch = "x86_64", target_env = "gnu"))]
+#[test]
+fn read_interpreter_of_libc() {
+    dlopen_rs::init();
+    let libc = ElfLibrary::load_existing("libc.so.6").unwrap();
+    assert_eq!(libc.interpreter(), Some("/lib64/ld-linux-x86-64.so.2"));
+}
+
+// 普通的动态库没有PT_INTERP
+#[test]
+fn library_without_interpreter() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("interpreter");
+    let path = common::build_lib(&dir, "libnointerp.so", "int value(void) { return 1; }", &[]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(lib.interpreter().is_none());
+}
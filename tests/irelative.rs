@@ -0,0 +1,25 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// 局部的ifunc不需要符号，ptr的初始值和plt中的地址都通过IRELATIVE重定位得到
+const SRC: &str = r#"
+static int impl(void) { return 9; }
+static void *resolve(void) { return impl; }
+static int f(void) __attribute__((ifunc("resolve")));
+int (*ptr)(void) = f;
+int call_ptr(void) { return ptr(); }
+int call(void) { return f(); }
+"#;
+
+#[test]
+fn resolve_irelative() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("irelative");
+    let path = common::build_lib(&dir, "libirelative.so", SRC, &[]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_NOW).unwrap();
+    let call_ptr = unsafe { lib.get::<extern "C" fn() -> i32>("call_ptr").unwrap() };
+    let call = unsafe { lib.get::<extern "C" fn() -> i32>("call").unwrap() };
+    assert_eq!(call_ptr(), 9);
+    assert_eq!(call(), 9);
+}
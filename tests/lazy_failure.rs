@@ -0,0 +1,35 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, LazyAction, OpenFlags};
+use std::sync::Mutex;
+
+static MISSING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+extern "C" fn stub() -> i32 {
+    7
+}
+
+// 延迟绑定时找不到符号，由handler提供替代的函数
+#[test]
+fn missing_lazy_symbol_uses_the_stub() {
+    dlopen_rs::init();
+    dlopen_rs::set_lazy_failure_handler(|name| {
+        MISSING.lock().unwrap().push(name.to_owned());
+        LazyAction::Return(stub as *const ())
+    });
+    let dir = common::fixture_dir("lazy_failure");
+    let path = common::build_lib(
+        &dir,
+        "liblazyfailure.so",
+        "int missing_func(void); int call_missing(void) { return missing_func(); }",
+        &[],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_LAZY).unwrap();
+    let call_missing = unsafe { lib.get::<extern "C" fn() -> i32>("call_missing").unwrap() };
+    assert!(MISSING.lock().unwrap().is_empty());
+    assert_eq!(call_missing(), 7);
+    assert_eq!(*MISSING.lock().unwrap(), ["missing_func"]);
+    // 之后的调用直接使用绑定好的地址
+    assert_eq!(call_missing(), 7);
+    assert_eq!(MISSING.lock().unwrap().len(), 1);
+}
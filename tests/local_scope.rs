@@ -0,0 +1,69 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// A和B有共同的依赖库，但是RTLD_LOCAL加载的A中的foo不能用于重定位B
+#[test]
+fn local_symbols_do_not_leak() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("local_scope");
+    common::build_lib(
+        &dir,
+        "liblocalshared.so",
+        "int shared(void) { return 1; }",
+        &[],
+    );
+    let a = common::build_lib(
+        &dir,
+        "liblocala.so",
+        "int shared(void); int foo(void) { return shared() + 1; }",
+        &["-Wl,--no-as-needed", "-llocalshared"],
+    );
+    let b = common::build_lib(
+        &dir,
+        "liblocalb.so",
+        "int shared(void); int foo(void); int call(void) { return shared() + foo(); }",
+        &["-Wl,--no-as-needed", "-llocalshared"],
+    );
+    let a = ElfLibrary::dlopen(a, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_NOW).unwrap();
+    let foo = unsafe { a.get::<extern "C" fn() -> i32>("foo").unwrap() };
+    assert_eq!(foo(), 2);
+    assert!(ElfLibrary::dlopen(b, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_NOW).is_err());
+}
+
+// 通过已加载的库间接依赖的库在之后的加载中仍然属于依赖库的作用域
+#[test]
+fn indirect_dependencies_stay_in_scope() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("local_scope");
+    common::build_lib(
+        &dir,
+        "libindirectshared.so",
+        "int indirect_shared(void) { return 1; }",
+        &[],
+    );
+    let a = common::build_lib(
+        &dir,
+        "libindirecta.so",
+        "int indirect_shared(void); int a(void) { return indirect_shared(); }",
+        &["-Wl,--no-as-needed", "-lindirectshared"],
+    );
+    let b = common::build_lib(
+        &dir,
+        "libindirectb.so",
+        "int a(void); int b(void) { return a() + 1; }",
+        &["-Wl,--no-as-needed", "-lindirecta"],
+    );
+    let c = common::build_lib(
+        &dir,
+        "libindirectc.so",
+        "int indirect_shared(void); int c(void) { return indirect_shared() + 2; }",
+        &["-Wl,--no-as-needed", "-lindirectshared"],
+    );
+    let flags = OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_NOW;
+    let _a = ElfLibrary::dlopen(a, flags).unwrap();
+    let _b = ElfLibrary::dlopen(b, flags).unwrap();
+    let c = ElfLibrary::dlopen(c, flags).unwrap();
+    let c = unsafe { c.get::<extern "C" fn() -> i32>("c").unwrap() };
+    assert_eq!(c(), 3);
+}
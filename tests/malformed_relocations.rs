@@ -0,0 +1,74 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const R_X86_64_IRELATIVE: u64 = 37;
+
+// 局部ifunc的地址会在.rela.dyn中产生IRELATIVE重定位，这类重定位由dlopen-rs处理
+const SRC: &str = r#"
+static int impl(void) { return 9; }
+static void *resolve(void) { return impl; }
+static int f(void) __attribute__((ifunc("resolve")));
+int (*ptr)(void) = f;
+"#;
+
+fn build(test: &str) -> Vec<u8> {
+    let dir = common::fixture_dir(test);
+    let path = common::build_lib(
+        &dir,
+        &format!("lib{}.so", test),
+        &format!("{}{}", common::PLT_CALL, SRC),
+        &[],
+    );
+    std::fs::read(path).unwrap()
+}
+
+fn load(bytes: &[u8], name: &str) -> bool {
+    ElfLibrary::from_binary(bytes, name, OpenFlags::CUSTOM_NOT_REGISTER)
+        .and_then(|lib| lib.relocate(&[]))
+        .is_ok()
+}
+
+// 写入位置超出库的映射范围的重定位项返回错误，而不是写入任意地址
+#[cfg(target_arch = "x86_64")]
+#[test]
+fn reject_out_of_bounds_offset() {
+    dlopen_rs::init();
+    let mut bytes = build("malformed_offset");
+    let rela = common::relocations(&bytes)
+        .into_iter()
+        .find(|rela| common::rela_info(&bytes, *rela) == R_X86_64_IRELATIVE)
+        .unwrap();
+    common::set_rela_offset(&mut bytes, rela, 1 << 40);
+    assert!(!load(&bytes, "libmalformed_offset.so"));
+}
+
+// 无法识别的重定位类型返回错误
+#[test]
+fn reject_unknown_type() {
+    dlopen_rs::init();
+    let mut bytes = build("malformed_type");
+    let rela = common::relocations(&bytes)
+        .into_iter()
+        .find(|rela| common::rela_info(&bytes, *rela) >> 32 != 0)
+        .unwrap();
+    let info = common::rela_info(&bytes, rela) & !0xffff_ffff | 0xfe;
+    common::set_rela_info(&mut bytes, rela, info);
+    assert!(!load(&bytes, "libmalformed_type.so"));
+}
+
+// DT_GNU_HASH中没有bucket时，查找符号会除以0，这样的库在重定位之前就返回错误
+#[test]
+fn reject_empty_gnu_hash() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("malformed_hash");
+    let path = common::build_lib(
+        &dir,
+        "libmalformed_hash.so",
+        &format!("{}int dep_value = 1;", common::PLT_CALL),
+        &["-Wl,--hash-style=gnu"],
+    );
+    let mut bytes = std::fs::read(path).unwrap();
+    common::set_gnu_hash_word(&mut bytes, 0, 0);
+    assert!(!load(&bytes, "libmalformed_hash.so"));
+}
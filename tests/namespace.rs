@@ -0,0 +1,28 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags, LM_ID_NEWLM};
+
+// 同一个库加载到两个命名空间中，各自拥有独立的静态变量
+#[test]
+fn same_library_in_two_namespaces() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("namespace");
+    let path = common::build_lib(
+        &dir,
+        "libnsstate.so",
+        "static int counter; int bump(void) { return ++counter; }",
+        &[],
+    );
+    let a = ElfLibrary::dlmopen(LM_ID_NEWLM, &path, OpenFlags::RTLD_LOCAL).unwrap();
+    let b = ElfLibrary::dlmopen(LM_ID_NEWLM, &path, OpenFlags::RTLD_LOCAL).unwrap();
+    assert_ne!(a.namespace(), b.namespace());
+    assert_ne!(a.base(), b.base());
+    let bump_a = unsafe { a.get::<extern "C" fn() -> i32>("bump").unwrap() };
+    let bump_b = unsafe { b.get::<extern "C" fn() -> i32>("bump").unwrap() };
+    assert_eq!(bump_a(), 1);
+    assert_eq!(bump_a(), 2);
+    assert_eq!(bump_b(), 1);
+    // 在同一个命名空间中再次打开得到的是同一个库
+    let again = ElfLibrary::dlmopen(a.namespace(), &path, OpenFlags::RTLD_LOCAL).unwrap();
+    assert_eq!(again.base(), a.base());
+}
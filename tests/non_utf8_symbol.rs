@@ -0,0 +1,38 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::ffi::c_void;
+
+// 通过asm标签定义一个名字不是合法UTF-8的符号
+const SRC: &str = r#"
+int bad_value(void) __asm__("non_utf8_value\xff");
+int bad_value(void) { return 3; }
+"#;
+
+#[test]
+fn get_bytes_finds_a_non_utf8_name() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("non_utf8_get_bytes");
+    let path = common::build_lib(&dir, "libnonutf8.so", SRC, &[]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let ptr = lib.get_bytes(b"non_utf8_value\xff").unwrap();
+    let bad_value: extern "C" fn() -> i32 = unsafe { core::mem::transmute(ptr) };
+    assert_eq!(bad_value(), 3);
+    assert!(lib.get_bytes(b"non_utf8_missing\xff").is_err());
+}
+
+// C调用者传入的符号名不是合法的UTF-8时，dlsym按字节查找而不是panic
+#[test]
+fn dlsym_finds_a_non_utf8_name() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("non_utf8_dlsym");
+    let path = common::build_lib(&dir, "libnonutf8.so", SRC, &[]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_NOW | OpenFlags::RTLD_GLOBAL).unwrap();
+    let expected = lib.get_bytes(b"non_utf8_value\xff").unwrap();
+    let found =
+        unsafe { dlopen_rs::dlopen::dlsym(core::ptr::null(), c"non_utf8_value\xff".as_ptr()) };
+    assert_eq!(found, expected as *const c_void);
+    let missing =
+        unsafe { dlopen_rs::dlopen::dlsym(core::ptr::null(), c"non_utf8_missing\xff".as_ptr()) };
+    assert!(missing.is_null());
+}
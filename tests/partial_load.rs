@@ -0,0 +1,62 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::ffi::{c_int, c_void, CStr};
+
+unsafe extern "C" fn collect(
+    info: *mut libc::dl_phdr_info,
+    _size: usize,
+    data: *mut c_void,
+) -> c_int {
+    let names = &mut *(data as *mut Vec<String>);
+    names.push(
+        CStr::from_ptr((*info).dlpi_name)
+            .to_string_lossy()
+            .into_owned(),
+    );
+    0
+}
+
+// dl_iterate_phdr会遍历所有注册的库，包括作为依赖库加载的库
+fn registry() -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    unsafe {
+        dlopen_rs::dlopen::dl_iterate_phdr(Some(collect), &mut names as *mut Vec<String> as _)
+    };
+    names
+}
+
+// 依赖图中间的库重定位失败时，已经加载的新库都会被移除，注册表和加载前一样
+#[test]
+fn failed_load_leaves_registry_unchanged() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("partial_load");
+    common::build_lib(
+        &dir,
+        "libpartialleaf.so",
+        "int leaf(void) { return 1; }",
+        &[],
+    );
+    common::build_lib(
+        &dir,
+        "libpartialbroken.so",
+        "int leaf(void); int missing_symbol(void); int broken(void) { return leaf() + missing_symbol(); }",
+        &["-Wl,--no-as-needed", "-lpartialleaf"],
+    );
+    let plugin = common::build_lib(
+        &dir,
+        "libpartialplugin.so",
+        "int broken(void); int plugin(void) { return broken(); }",
+        &["-Wl,--no-as-needed", "-lpartialbroken"],
+    );
+    let before = registry();
+    assert!(ElfLibrary::dlopen(&plugin, OpenFlags::RTLD_GLOBAL | OpenFlags::RTLD_NOW).is_err());
+    assert_eq!(registry(), before);
+    // 失败后可以单独加载没有问题的依赖库
+    let leaf = ElfLibrary::dlopen(dir.join("libpartialleaf.so"), OpenFlags::RTLD_LOCAL).unwrap();
+    let leaf = unsafe { leaf.get::<extern "C" fn() -> i32>("leaf").unwrap() };
+    assert_eq!(leaf(), 1);
+    assert!(registry()
+        .iter()
+        .any(|name| name.ends_with("libpartialleaf.so")));
+}
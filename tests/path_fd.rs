@@ -0,0 +1,29 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::os::unix::fs::OpenOptionsExt;
+
+// 通过O_PATH打开的目录fd和相对路径加载插件
+#[test]
+fn load_relative_to_an_o_path_dir_fd() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("path_fd");
+    common::build_lib(
+        &dir,
+        "libpathfd.so",
+        "int path_fd_value(void) { return 7; }",
+        &[],
+    );
+    let dir_fd = std::fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_PATH | libc::O_DIRECTORY)
+        .open(&dir)
+        .unwrap();
+    let lib = ElfLibrary::from_path_fd(&dir_fd, "libpathfd.so", OpenFlags::RTLD_NOW)
+        .unwrap()
+        .relocate(&[])
+        .unwrap();
+    let value = unsafe { lib.get::<extern "C" fn() -> i32>("path_fd_value").unwrap() };
+    assert_eq!(value(), 7);
+    assert!(ElfLibrary::from_path_fd(&dir_fd, "libmissing.so", OpenFlags::RTLD_NOW).is_err());
+}
@@ -0,0 +1,44 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const MOCK: &str = r#"
+#include <stddef.h>
+static char buf[4096];
+static size_t used;
+int calls;
+void *malloc(size_t size) {
+    calls++;
+    void *ptr = buf + used;
+    used += (size + 15) & ~15;
+    return ptr;
+}
+int malloc_calls(void) { return calls; }
+"#;
+
+const PLUGIN: &str = r#"
+#include <stdlib.h>
+void *alloc(void) { return malloc(16); }
+"#;
+
+// 预加载库中的malloc优先于libc中的定义
+#[test]
+fn preloaded_malloc_is_called_by_plugin() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("preload");
+    let mock = common::build_lib(
+        &dir,
+        "libmockmalloc.so",
+        &format!("{}{}", common::PLT_CALL, MOCK),
+        &[],
+    );
+    let plugin = common::build_lib(&dir, "libpreloadplugin.so", PLUGIN, &[]);
+    let mock = ElfLibrary::preload(mock).unwrap();
+    let lib = ElfLibrary::dlopen(plugin, OpenFlags::RTLD_NOW).unwrap();
+    let calls = unsafe { mock.get::<extern "C" fn() -> i32>("malloc_calls").unwrap() };
+    let alloc = unsafe { lib.get::<extern "C" fn() -> *mut u8>("alloc").unwrap() };
+    assert_eq!(calls(), 0);
+    assert!(!alloc().is_null());
+    assert!(!alloc().is_null());
+    assert_eq!(calls(), 2);
+}
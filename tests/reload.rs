@@ -0,0 +1,27 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+const SRC: &str = "static int counter; int bump(void) { return ++counter; }";
+
+// CUSTOM_NOT_REGISTER的库不会进入全局表，drop后可以用同一个路径重新加载
+#[test]
+fn reload_unregistered_library() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("reload");
+    let path = common::build_lib(&dir, "libreload.so", SRC, &[]);
+    for _ in 0..10 {
+        let lib = ElfLibrary::from_file(&path, OpenFlags::CUSTOM_NOT_REGISTER)
+            .unwrap()
+            .relocate(&[])
+            .unwrap();
+        let bump = unsafe { lib.get::<extern "C" fn() -> i32>("bump").unwrap() };
+        // 每次重新映射，静态变量都是新的
+        assert_eq!(bump(), 1);
+        assert_eq!(bump(), 2);
+        assert!(dlopen_rs::loaded_libraries()
+            .iter()
+            .all(|lib| lib.name() != path.to_str().unwrap()));
+        drop(lib);
+    }
+}
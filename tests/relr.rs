@@ -0,0 +1,41 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, Error, OpenFlags};
+
+// 连续的指针数组会被链接器压缩为DT_RELR中的位图
+const SRC: &str = r#"
+static int values[16] = {0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15};
+static int *ptrs[16] = {
+    &values[0], &values[1], &values[2], &values[3], &values[4], &values[5], &values[6], &values[7],
+    &values[8], &values[9], &values[10], &values[11], &values[12], &values[13], &values[14], &values[15],
+};
+int sum(void) {
+    int sum = 0;
+    for (int i = 0; i < 16; i++) sum += *ptrs[i];
+    return sum;
+}
+"#;
+
+#[test]
+fn apply_packed_relative_relocations() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("relr");
+    let path = common::build_lib(&dir, "librelr.so", SRC, &["-Wl,-z,pack-relative-relocs"]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let sum = unsafe { lib.get::<extern "C" fn() -> i32>("sum").unwrap() };
+    assert_eq!(sum(), 120);
+}
+
+// DT_RELR中超出库映射范围的地址返回错误，而不是写入任意地址
+#[test]
+fn reject_out_of_bounds_entry() {
+    const SHT_RELR: u32 = 19;
+    dlopen_rs::init();
+    let dir = common::fixture_dir("relr_out_of_bounds");
+    let path = common::build_lib(&dir, "librelr.so", SRC, &["-Wl,-z,pack-relative-relocs"]);
+    let mut bytes = std::fs::read(path).unwrap();
+    let (off, _) = common::sections(&bytes, SHT_RELR)[0];
+    bytes[off..off + 8].copy_from_slice(&(1u64 << 40).to_le_bytes());
+    let res = ElfLibrary::from_binary(&bytes, "librelr.so", OpenFlags::CUSTOM_NOT_REGISTER);
+    assert!(matches!(res, Err(Error::LoaderError { .. })));
+}
@@ -0,0 +1,37 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// 依赖库位于$ORIGIN/$LIB/$PLATFORM下，只有三个变量都被替换后才能找到
+#[test]
+fn expand_origin_lib_and_platform() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("rpath_tokens");
+    let lib = if cfg!(target_pointer_width = "64") {
+        "lib64"
+    } else {
+        "lib"
+    };
+    let dep_dir = dir.join(lib).join(std::env::consts::ARCH);
+    std::fs::create_dir_all(&dep_dir).unwrap();
+    common::build_lib(
+        &dep_dir,
+        "libtokendep.so",
+        "int dep_value(void) { return 7; }",
+        &[],
+    );
+    let path = common::build_lib(
+        &dir,
+        "libtokenplugin.so",
+        "int dep_value(void); int value(void) { return dep_value(); }",
+        &[
+            &format!("-L{}", dep_dir.display()),
+            "-Wl,--no-as-needed",
+            "-ltokendep",
+            "-Wl,-rpath,${ORIGIN}/$LIB/$PLATFORM",
+        ],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let value = unsafe { lib.get::<extern "C" fn() -> i32>("value").unwrap() };
+    assert_eq!(value(), 7);
+}
@@ -0,0 +1,34 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::process::Command;
+
+const SRC: &str = r#"
+#[no_mangle]
+pub extern "C" fn args_count() -> usize {
+    let args: Vec<String> = std::env::args().collect();
+    println!("{} args", args.len());
+    args.len()
+}
+"#;
+
+// Rust的std在初始化函数中保存argc和argv，之后std::env::args才能使用
+#[test]
+fn rust_cdylib_reads_args() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("rust_args");
+    let src = dir.join("args.rs");
+    std::fs::write(&src, SRC).unwrap();
+    let path = dir.join("libargs.so");
+    let rustc = std::env::var("RUSTC").unwrap_or(String::from("rustc"));
+    let status = Command::new(rustc)
+        .args(["--crate-type", "cdylib", "--edition", "2021", "-o"])
+        .arg(&path)
+        .arg(&src)
+        .status()
+        .unwrap();
+    assert!(status.success());
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let args_count = unsafe { lib.get::<extern "C" fn() -> usize>("args_count").unwrap() };
+    assert_eq!(args_count(), std::env::args().count());
+}
@@ -0,0 +1,26 @@
+#![cfg(feature = "sections")]
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+#[test]
+fn list_text_section() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("sections");
+    let path = common::build_lib(&dir, "libsections.so", "int value(void) { return 1; }", &[]);
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL).unwrap();
+    let sections = lib.sections();
+    let text = sections
+        .iter()
+        .find(|section| section.name == ".text")
+        .unwrap();
+    let start = lib.base() + text.offset.unwrap();
+    let value = unsafe { lib.get::<()>("value").unwrap().into_raw() } as usize;
+    assert!((start..start + text.size).contains(&value));
+    // .comment不会被映射到内存中
+    let comment = sections
+        .iter()
+        .find(|section| section.name == ".comment")
+        .unwrap();
+    assert_eq!(comment.offset, None);
+}
@@ -0,0 +1,38 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// 库以DT_SONAME注册，通过不同的文件名打开或作为依赖库时都是同一个库
+#[test]
+fn dedup_by_soname() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("soname");
+    let path = common::build_lib(
+        &dir,
+        "libsoname.so",
+        &format!(
+            "{}int counter; int bump(void) {{ return ++counter; }}",
+            common::PLT_CALL
+        ),
+        &["-Wl,-soname,libsoname.so.1"],
+    );
+    let link = dir.join("libsoname.so.1");
+    let _ = std::fs::remove_file(&link);
+    std::os::unix::fs::symlink(&path, &link).unwrap();
+    let plugin = common::build_lib(
+        &dir,
+        "libsonameplugin.so",
+        "int bump(void); int plugin_bump(void) { return bump(); }",
+        &["-Wl,--no-as-needed", "-lsoname"],
+    );
+
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LOCAL).unwrap();
+    let by_link = ElfLibrary::dlopen(&link, OpenFlags::RTLD_LOCAL).unwrap();
+    assert_eq!(by_link.base(), lib.base());
+
+    let plugin = ElfLibrary::dlopen(plugin, OpenFlags::RTLD_LOCAL).unwrap();
+    let bump = unsafe { lib.get::<extern "C" fn() -> i32>("bump").unwrap() };
+    let plugin_bump = unsafe { plugin.get::<extern "C" fn() -> i32>("plugin_bump").unwrap() };
+    assert_eq!(bump(), 1);
+    assert_eq!(plugin_bump(), 2);
+}
@@ -0,0 +1,109 @@
+#![cfg(feature = "version")]
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::path::PathBuf;
+
+// foo有V1和V2两个版本，V2是默认版本
+fn build_versioned(test: &str) -> PathBuf {
+    let dir = common::fixture_dir(test);
+    let script = dir.join("versions.map");
+    std::fs::write(
+        &script,
+        "V1 { global: foo; local: *; };\nV2 { global: foo; } V1;\n",
+    )
+    .unwrap();
+    common::build_lib(
+        &dir,
+        "libversioned.so",
+        r#"
+        int foo_v1(void) { return 1; }
+        int foo_v2(void) { return 2; }
+        __asm__(".symver foo_v1, foo@V1");
+        __asm__(".symver foo_v2, foo@@V2");
+        "#,
+        &[&format!("-Wl,--version-script={}", script.display())],
+    )
+}
+
+// Dylib::get返回默认版本的符号，而不是第一个找到的符号
+#[test]
+fn get_prefers_the_default_version() {
+    dlopen_rs::init();
+    let lib = ElfLibrary::dlopen(build_versioned("symver"), OpenFlags::RTLD_LOCAL).unwrap();
+    let mut versions = lib.symbol_versions("foo");
+    versions.sort();
+    assert_eq!(
+        versions,
+        [(String::from("V1"), false), (String::from("V2"), true)]
+    );
+    let foo = unsafe { lib.get::<extern "C" fn() -> i32>("foo").unwrap() };
+    assert_eq!(foo(), 2);
+}
+
+#[test]
+fn get_version_default_returns_the_version() {
+    dlopen_rs::init();
+    let lib = ElfLibrary::dlopen(build_versioned("symver_default"), OpenFlags::RTLD_LOCAL).unwrap();
+    let (foo, version) = unsafe {
+        lib.get_version_default::<extern "C" fn() -> i32>("foo")
+            .unwrap()
+    };
+    assert_eq!(version, "V2");
+    assert_eq!(foo(), 2);
+    let foo_v1 = unsafe {
+        lib.get_version::<extern "C" fn() -> i32>("foo", "V1")
+            .unwrap()
+    };
+    assert_eq!(foo_v1(), 1);
+    assert!(lib.symbol_versions("bar").is_empty());
+}
+
+// libversioned.so中foo的默认版本是V2，所以引用foo的库需要V2。
+// `v2`为false时，用只有V1的libversioned.so替换原来的库，使这个需求无法满足
+fn build_requirement(test: &str, v2: bool) -> PathBuf {
+    let provider = build_versioned(test);
+    let dir = provider.parent().unwrap().to_path_buf();
+    let user = common::build_lib(
+        &dir,
+        "libneedsv2.so",
+        "int foo(void); int call_foo(void) { return foo(); }",
+        &["-lversioned"],
+    );
+    if !v2 {
+        let script = dir.join("versions.map");
+        std::fs::write(&script, "V1 { global: foo; local: *; };\n").unwrap();
+        common::build_lib(
+            &dir,
+            "libversioned.so",
+            "int foo(void) { return 1; }",
+            &[&format!("-Wl,--version-script={}", script.display())],
+        );
+    }
+    user
+}
+
+#[test]
+fn version_requirements_are_satisfied() {
+    dlopen_rs::init();
+    let user = build_requirement("symver_satisfied", true);
+    let provider = user.with_file_name("libversioned.so");
+    let lib = ElfLibrary::dlopen(&user, OpenFlags::RTLD_LOCAL).unwrap();
+    let provider = ElfLibrary::dlopen(provider, OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(lib.check_version_requirements(&[provider]).is_ok());
+}
+
+// 延迟绑定时foo不会在加载时被解析，所以库可以加载成功，但版本需求无法满足
+#[test]
+fn unsatisfiable_version_requirement() {
+    dlopen_rs::init();
+    let user = build_requirement("symver_unsatisfiable", false);
+    let provider = user.with_file_name("libversioned.so");
+    let lib = ElfLibrary::dlopen(&user, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_LAZY).unwrap();
+    let provider = ElfLibrary::dlopen(provider, OpenFlags::RTLD_LOCAL).unwrap();
+    assert_eq!(
+        lib.check_version_requirements(&[provider]).unwrap_err(),
+        [(String::from("libversioned.so"), String::from("V2"))]
+    );
+    assert!(lib.check_version_requirements(&[]).is_err());
+}
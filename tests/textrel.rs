@@ -0,0 +1,27 @@
+#![cfg(not(feature = "deny-textrel"))]
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+// table位于.text中，对value的绝对地址引用会在只读的代码段上产生重定位(DT_TEXTREL)
+const SRC: &str = r#"
+int value = 42;
+__asm__(".text\n.balign 8\n.globl table\n.type table,@object\ntable: .quad value\n.previous\n");
+extern int *table;
+int get(void) { return *table; }
+"#;
+
+#[test]
+fn relocate_text_segment() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("textrel");
+    let path = common::build_lib(
+        &dir,
+        "libtextrel.so",
+        &format!("{}{}", common::PLT_CALL, SRC),
+        &["-Wl,-z,notext"],
+    );
+    let lib = ElfLibrary::dlopen(path, OpenFlags::RTLD_LOCAL | OpenFlags::RTLD_NOW).unwrap();
+    let get = unsafe { lib.get::<extern "C" fn() -> i32>("get").unwrap() };
+    assert_eq!(get(), 42);
+}
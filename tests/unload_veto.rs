@@ -0,0 +1,52 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+
+fn is_loaded(name: &str) -> bool {
+    dlopen_rs::loaded_libraries()
+        .iter()
+        .any(|lib| lib.name().ends_with(name))
+}
+
+fn setup(name: &str) -> std::path::PathBuf {
+    dlopen_rs::init();
+    // 只保留libkept.so，两个测试并行运行时也不会互相影响
+    dlopen_rs::set_unload_veto(|lib| lib.name().ends_with("libkept.so"));
+    let dir = common::fixture_dir("unload_veto");
+    common::build_lib(&dir, name, "int value(void) { return 1; }", &[])
+}
+
+#[test]
+fn veto_keeps_the_library_resident() {
+    let path = setup("libkept.so");
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LOCAL).unwrap();
+    let base = lib.base();
+    assert!(!lib.close().unwrap());
+    assert!(is_loaded("libkept.so"));
+    // 再次打开时使用的还是同一个库
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LOCAL).unwrap();
+    assert_eq!(lib.base(), base);
+    assert!(lib.is_resident());
+}
+
+#[test]
+fn veto_allows_unloading() {
+    let path = setup("libfreed.so");
+    let lib = ElfLibrary::dlopen(&path, OpenFlags::RTLD_LOCAL).unwrap();
+    assert!(lib.is_resident());
+    assert!(lib.close().unwrap());
+    assert!(!is_loaded("libfreed.so"));
+}
+
+#[test]
+fn dlclose_rejects_invalid_handles() {
+    dlopen_rs::init();
+    let mut not_a_handle = 0usize;
+    unsafe {
+        assert_ne!(dlopen_rs::dlopen::dlclose(core::ptr::null()), 0);
+        assert_ne!(
+            dlopen_rs::dlopen::dlclose(&mut not_a_handle as *mut usize as *const _),
+            0
+        );
+    }
+}
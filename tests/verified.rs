@@ -0,0 +1,34 @@
+#![cfg(feature = "verification")]
+mod common;
+
+use dlopen_rs::{ElfLibrary, Error, OpenFlags};
+use std::process::Command;
+
+fn sha256(path: &std::path::Path) -> [u8; 32] {
+    let output = Command::new("sha256sum").arg(path).output().unwrap();
+    let hex = String::from_utf8(output.stdout).unwrap();
+    let mut digest = [0; 32];
+    for (i, byte) in digest.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).unwrap();
+    }
+    digest
+}
+
+#[test]
+fn load_with_correct_and_tampered_digest() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("verified");
+    let path = common::build_lib(&dir, "libverified.so", "int value(void) { return 5; }", &[]);
+    let mut digest = sha256(&path);
+    let lib = ElfLibrary::from_file_verified(&path, &digest, OpenFlags::CUSTOM_NOT_REGISTER)
+        .unwrap()
+        .relocate(&[])
+        .unwrap();
+    let value = unsafe { lib.get::<extern "C" fn() -> i32>("value").unwrap() };
+    assert_eq!(value(), 5);
+    digest[0] ^= 1;
+    assert!(matches!(
+        ElfLibrary::from_file_verified(&path, &digest, OpenFlags::CUSTOM_NOT_REGISTER),
+        Err(Error::IntegrityError { .. })
+    ));
+}
@@ -0,0 +1,103 @@
+mod common;
+
+use dlopen_rs::{ElfLibrary, OpenFlags};
+use std::path::PathBuf;
+
+fn build(test: &str) -> PathBuf {
+    let dir = common::fixture_dir(test);
+    common::build_lib(
+        &dir,
+        "libweak.so",
+        "__attribute__((weak)) int foo(void) { return 1; }",
+        &[],
+    );
+    common::build_lib(&dir, "libstrong.so", "int foo(void) { return 2; }", &[]);
+    // 提供弱定义的库排在前面
+    common::build_lib(
+        &dir,
+        "libtop.so",
+        "extern int foo(void); int call_foo(void) { return foo(); }",
+        &["-Wl,--no-as-needed", "-lweak", "-lstrong"],
+    )
+}
+
+fn call_foo(lib: &dlopen_rs::Dylib) -> i32 {
+    let call_foo = unsafe { lib.get::<extern "C" fn() -> i32>("call_foo").unwrap() };
+    call_foo()
+}
+
+#[test]
+fn strong_definition_wins() {
+    dlopen_rs::init();
+    let top = build("weak_symbols_now");
+    let lib = ElfLibrary::dlopen(top, OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(call_foo(&lib), 2);
+}
+
+#[test]
+fn strong_definition_wins_with_lazy_binding() {
+    dlopen_rs::init();
+    let top = build("weak_symbols_lazy");
+    let lib = ElfLibrary::dlopen(top, OpenFlags::RTLD_LAZY).unwrap();
+    assert_eq!(call_foo(&lib), 2);
+}
+
+#[test]
+fn strong_definition_wins_in_relocate() {
+    dlopen_rs::init();
+    let top = build("weak_symbols_relocate");
+    let dir = top.parent().unwrap();
+    let load = |name: &str| {
+        ElfLibrary::from_file(dir.join(name), OpenFlags::CUSTOM_NOT_REGISTER)
+            .unwrap()
+            .relocate(&[])
+            .unwrap()
+    };
+    let weak = load("libweak.so");
+    let strong = load("libstrong.so");
+    let lib = ElfLibrary::from_file(top, OpenFlags::CUSTOM_NOT_REGISTER | OpenFlags::RTLD_NOW)
+        .unwrap()
+        .relocate([weak, strong])
+        .unwrap();
+    assert_eq!(call_foo(&lib), 2);
+}
+
+// libtop需要foo@V2，libversionedstrong中的强定义只有foo@V1，所以仍然使用libversionedweak中的弱定义
+#[cfg(feature = "version")]
+#[test]
+fn strong_definition_with_another_version_is_skipped() {
+    dlopen_rs::init();
+    let dir = common::fixture_dir("weak_symbols_version");
+    let script = |name: &str, version: &str| {
+        let path = dir.join(name);
+        std::fs::write(
+            &path,
+            format!("{} {{ global: foo; local: *; }};\n", version),
+        )
+        .unwrap();
+        format!("-Wl,--version-script={}", path.display())
+    };
+    common::build_lib(
+        &dir,
+        "libversionedweak.so",
+        "__attribute__((weak)) int foo(void) { return 1; }",
+        &[&script("weak.map", "V2")],
+    );
+    common::build_lib(
+        &dir,
+        "libversionedstrong.so",
+        r#"
+        int foo_v1(void) { return 3; }
+        __asm__(".symver foo_v1, foo@V1");
+        "#,
+        &[&script("strong.map", "V1")],
+    );
+    let top = common::build_lib(
+        &dir,
+        "libversionedtop.so",
+        "extern int foo(void); int call_foo(void) { return foo(); }",
+        &["-Wl,--no-as-needed", "-lversionedweak", "-lversionedstrong"],
+    );
+    let lib = ElfLibrary::dlopen(top, OpenFlags::RTLD_NOW).unwrap();
+    assert_eq!(call_foo(&lib), 1);
+}